@@ -3,7 +3,13 @@
 pub mod builder;
 pub mod default_ctx;
 pub mod exec;
+pub mod profile;
+pub mod replay;
+pub mod state_override;
 
 pub use builder::ZkBuilder;
 pub use default_ctx::DefaultZk;
-pub use exec::{ZkContextTr, ZkError};
+pub use exec::{ZkApiError, ZkContextTr, ZkError};
+pub use profile::{GasProfile, GasProfiler};
+pub use replay::BlockReplayResult;
+pub use state_override::StorageOverrideDb;
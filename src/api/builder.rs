@@ -1,5 +1,7 @@
 //! Builder trait [`ZkBuilder`] used to build [`ZKsyncEvm`].
-use crate::{ZkSpecId, evm::ZKsyncEvm, precompiles::ZKsyncPrecompiles, transaction::ZkTxTr};
+use crate::{
+    ZkLocalContext, ZkSpecId, evm::ZKsyncEvm, precompiles::ZKsyncPrecompiles, transaction::ZkTxTr,
+};
 use revm::{
     Context, Database,
     context::Cfg,
@@ -28,7 +30,8 @@ pub trait ZkBuilder: Sized {
     ) -> DefaultZKsyncEvm<Self::Context, INSP>;
 }
 
-impl<BLOCK, TX, CFG, DB, JOURNAL> ZkBuilder for Context<BLOCK, TX, CFG, DB, JOURNAL>
+impl<BLOCK, TX, CFG, DB, JOURNAL, CHAIN> ZkBuilder
+    for Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN, ZkLocalContext>
 where
     BLOCK: Block,
     TX: ZkTxTr,
@@ -1,5 +1,5 @@
 //! Contains trait [`DefaultZk`] used to create a default context.
-use crate::{ZKsyncTx, ZkSpecId};
+use crate::{ZKsyncTx, ZkLocalContext, ZkSpecId};
 use revm::{
     Context, Journal, MainContext,
     context::{BlockEnv, CfgEnv, TxEnv},
@@ -7,7 +7,8 @@ use revm::{
 };
 
 /// Type alias for the default context type of the ZKsyncEvm.
-pub type ZkContext<DB> = Context<BlockEnv, ZKsyncTx<TxEnv>, CfgEnv<ZkSpecId>, DB, Journal<DB>>;
+pub type ZkContext<DB> =
+    Context<BlockEnv, ZKsyncTx<TxEnv>, CfgEnv<ZkSpecId>, DB, Journal<DB>, (), ZkLocalContext>;
 
 /// Trait that allows for a default context to be created.
 pub trait DefaultZk {
@@ -17,9 +18,19 @@ pub trait DefaultZk {
 
 impl DefaultZk for ZkContext<EmptyDB> {
     fn default() -> Self {
-        Context::mainnet()
+        let ctx = Context::mainnet()
             .with_tx(ZKsyncTx::builder().build_fill())
-            .with_cfg(CfgEnv::new_with_spec(ZkSpecId::Atlas))
+            .with_cfg(CfgEnv::new_with_spec(ZkSpecId::Atlas));
+        // swap the mainnet local context for the ZKsync OS one
+        Context {
+            block: ctx.block,
+            tx: ctx.tx,
+            cfg: ctx.cfg,
+            journaled_state: ctx.journaled_state,
+            chain: ctx.chain,
+            local: ZkLocalContext::default(),
+            error: ctx.error,
+        }
     }
 }
 
@@ -1,13 +1,20 @@
 //! Implementation of the [`ExecuteEvm`] trait for the [`ZKsyncEvm`].
 use crate::{
-    ZKsyncTxError, ZkHaltReason, ZkSpecId, evm::ZKsyncEvm, handler::ZKsyncHandler,
+    ExecutionMode, ZKsyncTxError, ZkHaltReason, ZkLocalContext, ZkSpecId,
+    evm::ZKsyncEvm,
+    handler::ZKsyncHandler,
+    precompiles::{
+        BOOTLOADER_FORMAL_ADDRESS, ZKSYNC_SYSTEM_CONTRACTS, ZkPrecompileError,
+        l1_messenger::l1_message_sent_log,
+    },
     transaction::ZkTxTr,
 };
+use core::{fmt::Display, time::Duration};
 use revm::{
     DatabaseCommit, ExecuteCommitEvm, ExecuteEvm,
     context::{ContextSetters, result::ExecResultAndState},
     context_interface::{
-        Cfg, ContextTr, Database, JournalTr,
+        Block, Cfg, ContextTr, Database, JournalTr, Transaction,
         result::{EVMError, ExecutionResult},
     },
     handler::{
@@ -18,24 +25,348 @@ use revm::{
         InspectCommitEvm, InspectEvm, InspectSystemCallEvm, Inspector, InspectorHandler, JournalExt,
     },
     interpreter::{InterpreterResult, interpreter::EthInterpreter},
-    primitives::{Address, Bytes},
-    state::EvmState,
+    primitives::{Address, B256, Bytes, U256, keccak256},
+    state::{Bytecode, EvmState},
+};
+use std::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
 };
 
 /// Type alias for ZKsync OS context
 pub trait ZkContextTr:
-    ContextTr<Journal: JournalTr<State = EvmState>, Tx: ZkTxTr, Cfg: Cfg<Spec = ZkSpecId>>
+    ContextTr<
+        Journal: JournalTr<State = EvmState>,
+        Tx: ZkTxTr,
+        Cfg: Cfg<Spec = ZkSpecId>,
+        Local = ZkLocalContext,
+    >
 {
+    /// Base fee of the current block.
+    fn current_base_fee(&self) -> u64 {
+        self.block().basefee()
+    }
+
+    /// Gas limit of the current block, or the [spec default](ZkSpecId::default_block_gas_limit)
+    /// if the block does not set one.
+    fn current_block_gas_limit(&self) -> u64 {
+        match self.block().gas_limit() {
+            0 => self.cfg().spec().default_block_gas_limit(),
+            gas_limit => gas_limit,
+        }
+    }
+
+    /// Blob gas price of the current block, zero if blobs are not priced.
+    fn current_blob_gas_price(&self) -> u128 {
+        self.block().blob_gasprice().unwrap_or_default()
+    }
+
+    /// Gas price the current transaction pays at the base fee of the current block.
+    fn current_effective_gas_price(&self) -> u128 {
+        self.tx()
+            .effective_gas_price(self.current_base_fee() as u128)
+    }
+
+    /// Number of pubdata bytes written by the current transaction.
+    fn pubdata_written(&self) -> u64 {
+        self.local().pubdata_written
+    }
+
+    /// Adds `bytes` to the pubdata written by the current transaction.
+    fn add_pubdata_written(&mut self, bytes: u64) {
+        let local = self.local_mut();
+        local.pubdata_written = local.pubdata_written.saturating_add(bytes);
+    }
+
+    /// Emits the `L1MessageSent` event of an L2 -> L1 `message` sent by `sender`, records it
+    /// and counts it as pubdata. Returns the message hash.
+    ///
+    /// Fails without recording anything if the transaction already sent
+    /// [`ZkSpecId::max_l1_messages_per_tx`] messages.
+    fn log_l2_to_l1_message(
+        &mut self,
+        sender: Address,
+        message: &[u8],
+    ) -> Result<B256, ZkPrecompileError> {
+        let limit = self.cfg().spec().max_l1_messages_per_tx();
+        if self.local().l2_to_l1_messages.len() >= limit {
+            return Err(ZkPrecompileError::TooManyL1Messages { limit });
+        }
+        let hash = keccak256(message);
+        self.journal_mut()
+            .log(l1_message_sent_log(sender, hash, message));
+        self.local_mut().l2_to_l1_messages.push(hash);
+        self.add_pubdata_written(message.len() as u64);
+        Ok(hash)
+    }
+
+    /// Hashes of the L2 -> L1 messages sent by the current transaction, in order.
+    fn l2_to_l1_messages(&self) -> &[B256] {
+        &self.local().l2_to_l1_messages
+    }
+
+    /// Makes `code` available to the current transaction by its hash.
+    fn save_bytecode(&mut self, code: Bytecode) {
+        let hash = code.hash_slow();
+        self.local_mut().bytecodes.insert(hash, code);
+    }
+
+    /// Returns the bytecode saved with [`Self::save_bytecode`] for the current transaction.
+    fn get_bytecode_by_hash(&self, hash: B256) -> Option<&Bytecode> {
+        self.local().bytecodes.get(&hash)
+    }
+
+    /// Hash of the current transaction, as set by [`Self::set_transaction_hash`].
+    ///
+    /// The hash is not computed from the transaction, as the context has no access to the
+    /// signed transaction encoding.
+    fn current_transaction_hash(&self) -> Option<B256> {
+        self.local().transaction_hash
+    }
+
+    /// Sets the hash of the current transaction, e.g. as received over RPC.
+    ///
+    /// Cleared together with the rest of the local context after the transaction.
+    fn set_transaction_hash(&mut self, hash: B256) {
+        self.local_mut().transaction_hash = Some(hash);
+    }
+
+    /// Mode the current transaction is executed in.
+    fn execution_mode(&self) -> ExecutionMode {
+        self.local().execution_mode
+    }
+
+    /// Sets the mode the next transaction is executed in.
+    ///
+    /// Reset to [`ExecutionMode::Execute`] together with the rest of the local context after
+    /// the transaction.
+    fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        self.local_mut().execution_mode = mode;
+    }
+
+    /// Nonce forced for `address` with [`Self::set_forced_nonce`], if any.
+    fn forced_nonce(&self, address: Address) -> Option<u64> {
+        self.local().forced_nonces.get(&address).copied()
+    }
+
+    /// Accepts a transaction from `address` with `nonce` regardless of the account nonce, e.g.
+    /// when replaying historical transactions out of order.
+    ///
+    /// Cleared together with the rest of the local context after the transaction.
+    fn set_forced_nonce(&mut self, address: Address, nonce: u64) {
+        self.local_mut().forced_nonces.insert(address, nonce);
+    }
+
+    /// Removes the nonce forced with [`Self::set_forced_nonce`] for `address`.
+    fn clear_forced_nonce(&mut self, address: Address) {
+        self.local_mut().forced_nonces.remove(&address);
+    }
+
+    /// Returns `true` if the current transaction calls a natively implemented system contract.
+    fn is_system_contract_call(&self) -> bool {
+        self.tx()
+            .kind()
+            .to()
+            .is_some_and(|target| ZKSYNC_SYSTEM_CONTRACTS.contains(target))
+    }
+
+    /// Returns `true` if the current transaction calls a system contract or is sent by the
+    /// bootloader.
+    fn requires_system_context(&self) -> bool {
+        self.is_system_contract_call() || self.tx().caller() == BOOTLOADER_FORMAL_ADDRESS
+    }
+
+    /// Reads the given accounts from the database, so a caching database serves them from
+    /// memory during execution.
+    ///
+    /// The journal is not touched: the accounts stay cold for EIP-2929 and gas is unaffected.
+    fn preload_account_list(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<(), <Self::Db as Database>::Error> {
+        for address in addresses {
+            self.db_mut().basic(*address)?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`Self::preload_account_list`], but for the given storage slots and their accounts.
+    fn preload_storage_list(
+        &mut self,
+        slots: &[(Address, U256)],
+    ) -> Result<(), <Self::Db as Database>::Error> {
+        for (address, key) in slots {
+            let db = self.db_mut();
+            db.basic(*address)?;
+            db.storage(*address, *key)?;
+        }
+        Ok(())
+    }
+
+    /// Preloads the callers and call targets of the given transactions and the ZKsync OS
+    /// system contracts with [`Self::preload_account_list`], e.g. before executing a block.
+    fn preload_from_tx_list(
+        &mut self,
+        txs: &[Self::Tx],
+    ) -> Result<(), <Self::Db as Database>::Error> {
+        let mut addresses: Vec<Address> = txs
+            .iter()
+            .flat_map(|tx| [Some(tx.caller()), tx.kind().to().copied()])
+            .flatten()
+            .chain(ZKSYNC_SYSTEM_CONTRACTS)
+            .collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        self.preload_account_list(&addresses)
+    }
+
+    /// Finalizes the journal and commits the resulting state to the database.
+    fn commit_to_db(&mut self)
+    where
+        Self::Db: DatabaseCommit,
+    {
+        let state = self.journal_mut().finalize();
+        self.db_mut().commit(state);
+    }
+
+    /// Same as [`Self::commit_to_db`], but also returns the committed state diff.
+    fn commit_to_db_with_state_diff(&mut self) -> EvmState
+    where
+        Self::Db: DatabaseCommit,
+    {
+        let state = self.journal_mut().finalize();
+        self.db_mut().commit(state.clone());
+        state
+    }
+
+    /// Returns the state [`Self::commit_to_db`] would commit, without touching the journal
+    /// or the database.
+    fn commit_to_db_dry_run(&self) -> EvmState
+    where
+        Self::Journal: JournalExt,
+    {
+        self.journal_ref().evm_state().clone()
+    }
 }
 
 impl<T> ZkContextTr for T where
-    T: ContextTr<Journal: JournalTr<State = EvmState>, Tx: ZkTxTr, Cfg: Cfg<Spec = ZkSpecId>>
+    T: ContextTr<
+            Journal: JournalTr<State = EvmState>,
+            Tx: ZkTxTr,
+            Cfg: Cfg<Spec = ZkSpecId>,
+            Local = ZkLocalContext,
+        >
 {
 }
 
 /// Type alias for the error type of the ZKsyncEvm.
 pub type ZkError<CTX> = EVMError<<<CTX as ContextTr>::Db as Database>::Error, ZKsyncTxError>;
 
+/// Error returned by the ZKsync OS API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZkApiError<DBError> {
+    /// Error returned by the EVM execution.
+    Execution(EVMError<DBError, ZKsyncTxError>),
+    /// Input rejected before it reached the EVM.
+    InvalidInput(String),
+    /// Error returned by the database outside of the EVM execution.
+    DatabaseError(DBError),
+    /// Error wrapped with additional context, see [`ZkApiError::context`].
+    WithContext {
+        /// Description of what was being done when the error occurred.
+        context: String,
+        /// Underlying error.
+        error: Box<ZkApiError<DBError>>,
+    },
+}
+
+impl<DBError> ZkApiError<DBError> {
+    /// Returns `true` if the error may go away on retry.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::WithContext { error, .. } => error.is_transient(),
+            _ => matches!(
+                self,
+                Self::DatabaseError(_) | Self::Execution(EVMError::Database(_))
+            ),
+        }
+    }
+
+    /// Returns `true` if the transaction or block failed validation.
+    pub fn is_validation_error(&self) -> bool {
+        match self {
+            Self::WithContext { error, .. } => error.is_validation_error(),
+            _ => matches!(
+                self,
+                Self::Execution(EVMError::Transaction(_) | EVMError::Header(_))
+            ),
+        }
+    }
+
+    /// Returns `true` if resubmitting the same request later may succeed.
+    pub fn is_retriable(&self) -> bool {
+        self.suggested_retry_delay().is_some()
+    }
+
+    /// Returns how long to wait before retrying, or `None` if retrying is pointless.
+    ///
+    /// Database errors are retried quickly. Transaction errors are retriable as defined by
+    /// [`ZKsyncTxError::is_retriable`]: a nonce ahead of the state may be filled by a pending
+    /// transaction, so it is retried once the next block is likely produced.
+    pub fn suggested_retry_delay(&self) -> Option<Duration> {
+        match self {
+            Self::DatabaseError(_) | Self::Execution(EVMError::Database(_)) => {
+                Some(Duration::from_millis(100))
+            }
+            Self::Execution(EVMError::Transaction(error)) => {
+                error.is_retriable().then_some(Duration::from_secs(1))
+            }
+            Self::WithContext { error, .. } => error.suggested_retry_delay(),
+            _ => None,
+        }
+    }
+
+    /// Wraps the error with a description of what was being done when it occurred.
+    pub fn context(self, context: impl Display) -> Self {
+        Self::WithContext {
+            context: context.to_string(),
+            error: Box::new(self),
+        }
+    }
+
+    /// Converts the error into the [`EVMError`] returned by the EVM.
+    ///
+    /// Errors raised outside of the EVM are mapped to their closest [`EVMError`] variant.
+    pub fn unwrap_execution(self) -> EVMError<DBError, ZKsyncTxError> {
+        match self {
+            Self::Execution(error) => error,
+            Self::InvalidInput(message) => EVMError::Custom(message),
+            Self::DatabaseError(error) => EVMError::Database(error),
+            Self::WithContext { error, .. } => error.unwrap_execution(),
+        }
+    }
+}
+
+impl<DBError: Display> Display for ZkApiError<DBError> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Execution(error) => error.fmt(f),
+            Self::InvalidInput(message) => write!(f, "invalid input: {message}"),
+            Self::DatabaseError(error) => write!(f, "database error: {error}"),
+            Self::WithContext { context, error } => write!(f, "{context}: {error}"),
+        }
+    }
+}
+
+impl<DBError: core::fmt::Debug + Display> core::error::Error for ZkApiError<DBError> {}
+
+impl<DBError> From<EVMError<DBError, ZKsyncTxError>> for ZkApiError<DBError> {
+    fn from(value: EVMError<DBError, ZKsyncTxError>) -> Self {
+        Self::Execution(value)
+    }
+}
+
 impl<CTX, INSP, PRECOMPILE> ExecuteEvm
     for ZKsyncEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
 where
@@ -157,3 +488,287 @@ where
         h.inspect_run_system_call(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultZk, ZKsyncTx, api::builder::ZkBuilder};
+    use revm::{
+        Context,
+        context::{LocalContextTr, TxEnv},
+        context_interface::result::InvalidTransaction,
+        database::{CacheDB, EmptyDB},
+        database_interface::DBErrorMarker,
+        primitives::{TxKind, U256},
+        state::AccountInfo,
+    };
+    use std::vec;
+
+    #[test]
+    fn current_block_gas_limit() {
+        let mut ctx = Context::default();
+        ctx.block.gas_limit = 30_000_000;
+        assert_eq!(ctx.current_block_gas_limit(), 30_000_000);
+        ctx.block.gas_limit = 0;
+        assert_eq!(
+            ctx.current_block_gas_limit(),
+            ZkSpecId::Atlas.default_block_gas_limit()
+        );
+    }
+
+    #[test]
+    fn log_l2_to_l1_message() {
+        let mut ctx = Context::default();
+        let sender = Address::with_last_byte(0x42);
+        let first = ctx.log_l2_to_l1_message(sender, b"first").unwrap();
+        let second = ctx.log_l2_to_l1_message(sender, &[0xab; 40]).unwrap();
+        assert_eq!(first, keccak256(b"first"));
+        assert_eq!(ctx.l2_to_l1_messages(), [first, second]);
+        assert_eq!(ctx.pubdata_written(), 45);
+
+        let logs = ctx.journal_mut().take_logs();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].topics()[2], first);
+        // Offset, length and the message padded to 32 bytes.
+        assert_eq!(logs[1].data.data.len(), 32 + 32 + 64);
+    }
+
+    #[test]
+    fn l1_messages_per_tx_are_limited() {
+        let mut ctx = Context::default();
+        let sender = Address::with_last_byte(0x42);
+        let limit = ZkSpecId::Atlas.max_l1_messages_per_tx();
+        assert_eq!(limit, 16);
+        for i in 0..limit {
+            assert!(ctx.log_l2_to_l1_message(sender, &[i as u8]).is_ok());
+        }
+        assert_eq!(
+            ctx.log_l2_to_l1_message(sender, b"one too many"),
+            Err(ZkPrecompileError::TooManyL1Messages { limit })
+        );
+        // The refused message is not recorded.
+        assert_eq!(ctx.l2_to_l1_messages().len(), limit);
+        assert_eq!(ctx.journal_mut().take_logs().len(), limit);
+
+        // The limit is per transaction.
+        ctx.local_mut().clear();
+        assert!(ctx.log_l2_to_l1_message(sender, b"next tx").is_ok());
+    }
+
+    #[test]
+    fn system_context() {
+        let user = Address::with_last_byte(0x42);
+        for (caller, target, system_call, system_context) in [
+            (user, user, false, false),
+            (user, ZKSYNC_SYSTEM_CONTRACTS[0], true, true),
+            (BOOTLOADER_FORMAL_ADDRESS, user, false, true),
+            (
+                BOOTLOADER_FORMAL_ADDRESS,
+                ZKSYNC_SYSTEM_CONTRACTS[0],
+                true,
+                true,
+            ),
+        ] {
+            let ctx = Context::default().with_tx(
+                ZKsyncTx::builder()
+                    .base(TxEnv::builder().caller(caller).kind(TxKind::Call(target)))
+                    .build_fill(),
+            );
+            assert_eq!(ctx.is_system_contract_call(), system_call);
+            assert_eq!(ctx.requires_system_context(), system_context);
+        }
+    }
+
+    #[test]
+    fn commit_to_db() {
+        let mut ctx = Context::default().with_db(CacheDB::<EmptyDB>::default());
+        let address = Address::with_last_byte(0x42);
+        ctx.journal_mut()
+            .balance_incr(address, U256::from(100))
+            .unwrap();
+
+        let dry_run = ctx.commit_to_db_dry_run();
+        assert!(
+            ctx.db()
+                .cache
+                .accounts
+                .get(&address)
+                .is_none_or(|account| account.info.balance.is_zero())
+        );
+
+        let state = ctx.commit_to_db_with_state_diff();
+        assert_eq!(state, dry_run);
+        assert_eq!(
+            ctx.db().cache.accounts[&address].info.balance,
+            U256::from(100)
+        );
+    }
+
+    #[test]
+    fn current_gas_price() {
+        let mut ctx = Context::default();
+        ctx.block.basefee = 10;
+        ctx.tx.base.tx_type = 2;
+        ctx.tx.base.gas_price = 100;
+        ctx.tx.base.gas_priority_fee = Some(5);
+        assert_eq!(ctx.current_base_fee(), 10);
+        assert_eq!(ctx.current_effective_gas_price(), 15);
+        assert_eq!(
+            ctx.current_blob_gas_price(),
+            ctx.block.blob_gasprice().unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn preloading_keeps_gas() {
+        let other = Address::with_last_byte(0x43);
+        // PUSH20 other BALANCE POP STOP
+        let mut code = vec![0x73];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[0x31, 0x50, 0x00]);
+        let contract = Address::with_last_byte(0x42);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(
+            contract,
+            AccountInfo::default().with_code(Bytecode::new_legacy(code.into())),
+        );
+        let tx = ZKsyncTx::builder()
+            .base(TxEnv::builder().kind(TxKind::Call(contract)))
+            .build_fill();
+
+        let mut evm = Context::default().with_db(db.clone()).build_zk();
+        let gas_used = evm.transact(tx.clone()).unwrap().result.gas_used();
+
+        // Preloading fills the database cache only, the access is still charged as cold.
+        let mut evm = Context::default().with_db(db).build_zk();
+        evm.0.ctx.preload_from_tx_list(&[tx.clone()]).unwrap();
+        evm.0.ctx.preload_account_list(&[other]).unwrap();
+        evm.0
+            .ctx
+            .preload_storage_list(&[(other, U256::ZERO)])
+            .unwrap();
+        assert!(evm.0.ctx.db().cache.accounts.contains_key(&other));
+        assert_eq!(evm.transact(tx).unwrap().result.gas_used(), gas_used);
+    }
+
+    #[test]
+    fn zk_error_classification() {
+        let db: ZkApiError<String> = ZkApiError::DatabaseError("timeout".into());
+        assert!(db.is_transient());
+        assert!(!db.is_validation_error());
+        assert_eq!(db.to_string(), "database error: timeout");
+        assert_eq!(db.unwrap_execution(), EVMError::Database("timeout".into()));
+
+        let tx: ZkApiError<String> = ZkApiError::Execution(EVMError::Transaction(
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 }),
+        ));
+        assert!(!tx.is_transient());
+        assert!(tx.is_validation_error());
+
+        let input: ZkApiError<String> = ZkApiError::InvalidInput("empty block".into());
+        assert!(!input.is_transient());
+        assert!(!input.is_validation_error());
+        assert_eq!(
+            input.unwrap_execution(),
+            EVMError::Custom("empty block".into())
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct DbUnavailable;
+
+    impl Display for DbUnavailable {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("database unavailable")
+        }
+    }
+
+    impl core::error::Error for DbUnavailable {}
+
+    impl DBErrorMarker for DbUnavailable {}
+
+    /// Database failing every read.
+    struct FailingDb;
+
+    impl Database for FailingDb {
+        type Error = DbUnavailable;
+
+        fn basic(&mut self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            Err(DbUnavailable)
+        }
+
+        fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            Err(DbUnavailable)
+        }
+
+        fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+            Err(DbUnavailable)
+        }
+
+        fn block_hash(&mut self, _number: u64) -> Result<B256, Self::Error> {
+            Err(DbUnavailable)
+        }
+    }
+
+    #[test]
+    fn zk_error_from_failing_db() {
+        let mut evm = Context::default().with_db(FailingDb).build_zk();
+        let error: ZkApiError<DbUnavailable> = evm
+            .transact(ZKsyncTx::builder().build_fill())
+            .unwrap_err()
+            .into();
+        assert_eq!(
+            error,
+            ZkApiError::Execution(EVMError::Database(DbUnavailable))
+        );
+        assert!(error.is_transient());
+        assert!(error.is_retriable());
+        assert!(!error.is_validation_error());
+
+        let error = error.context("executing tx 0");
+        assert_eq!(
+            error.to_string(),
+            "executing tx 0: database error: database unavailable"
+        );
+        assert!(error.is_transient());
+        assert_eq!(error.unwrap_execution(), EVMError::Database(DbUnavailable));
+    }
+
+    #[test]
+    fn zk_error_retry() {
+        let db: ZkApiError<String> = ZkApiError::DatabaseError("timeout".into());
+        assert!(db.is_retriable());
+        assert_eq!(db.suggested_retry_delay(), Some(Duration::from_millis(100)));
+
+        let nonce: ZkApiError<String> = ZkApiError::Execution(EVMError::Transaction(
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 }),
+        ));
+        assert_eq!(nonce.suggested_retry_delay(), Some(Duration::from_secs(1)));
+
+        let low: ZkApiError<String> = ZkApiError::Execution(EVMError::Transaction(
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooLow { tx: 1, state: 2 }),
+        ));
+        assert!(!low.is_retriable());
+        assert!(!ZkApiError::<String>::InvalidInput("empty block".into()).is_retriable());
+
+        // Transaction errors are retriable exactly when `ZKsyncTxError::is_retriable` says so.
+        for error in [
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 }),
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooLow { tx: 1, state: 2 }),
+            ZKsyncTxError::Base(InvalidTransaction::GasPriceLessThanBasefee),
+        ] {
+            let retriable = error.is_retriable();
+            let api: ZkApiError<String> = ZkApiError::Execution(EVMError::Transaction(error));
+            assert_eq!(api.is_retriable(), retriable);
+        }
+
+        let wrapped = db.clone().context("loading block 7");
+        assert_eq!(
+            wrapped.to_string(),
+            "loading block 7: database error: timeout"
+        );
+        assert!(wrapped.is_transient());
+        assert!(wrapped.is_retriable());
+        assert_eq!(wrapped.unwrap_execution(), db.unwrap_execution());
+    }
+}
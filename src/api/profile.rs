@@ -0,0 +1,161 @@
+//! Gas profiling of ZKsync OS transactions.
+use crate::{
+    ZkHaltReason,
+    api::exec::{ZkContextTr, ZkError},
+    evm::ZKsyncEvm,
+    precompiles::ZKSYNC_SYSTEM_CONTRACTS,
+    transaction::ZkTxTr,
+};
+use revm::{
+    Inspector,
+    context::ContextSetters,
+    context_interface::{Transaction, result::ExecutionResult},
+    handler::{PrecompileProvider, instructions::EthInstructions},
+    inspector::{InspectEvm, JournalExt},
+    interpreter::{
+        CallInputs, CallOutcome, InterpreterResult,
+        gas::{STANDARD_TOKEN_COST, get_tokens_in_calldata},
+        interpreter::EthInterpreter,
+    },
+};
+
+/// Gas used by a transaction, broken down by category. See [`ZKsyncEvm::profile_execution`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasProfile {
+    /// Gas not attributed to any other category: the base cost and the user code.
+    pub evm_execution: u64,
+    /// Gas charged for the calldata tokens.
+    pub calldata_gas: u64,
+    /// Gas charged for hashing the factory deps.
+    pub factory_deps_hashing_gas: u64,
+    /// Gas spent inside the natively implemented system contracts.
+    pub system_contract_gas: u64,
+    /// Gas used by the transaction.
+    pub total: u64,
+}
+
+/// Inspector summing the gas spent inside the ZKsync OS system contracts.
+#[derive(Clone, Debug, Default)]
+pub struct GasProfiler {
+    system_contract_gas: u64,
+}
+
+impl GasProfiler {
+    /// Gas spent inside the system contracts since the last [`Self::reset`].
+    pub fn system_contract_gas(&self) -> u64 {
+        self.system_contract_gas
+    }
+
+    /// Resets the collected gas.
+    pub fn reset(&mut self) {
+        self.system_contract_gas = 0;
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for GasProfiler {
+    fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        if ZKSYNC_SYSTEM_CONTRACTS.contains(&inputs.bytecode_address) {
+            let spent = inputs
+                .gas_limit
+                .saturating_sub(outcome.result.gas.remaining());
+            self.system_contract_gas = self.system_contract_gas.saturating_add(spent);
+        }
+    }
+}
+
+impl<CTX, PRECOMPILE> ZKsyncEvm<CTX, GasProfiler, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: ZkContextTr<Journal: JournalExt> + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    /// Executes the transaction and breaks the gas it used down by category, e.g. for block
+    /// fee analysis.
+    ///
+    /// Like [`InspectEvm::inspect_one_tx`], the state changes are left in the journal.
+    pub fn profile_execution(
+        &mut self,
+        tx: CTX::Tx,
+    ) -> Result<(ExecutionResult<ZkHaltReason>, GasProfile), ZkError<CTX>> {
+        let calldata_gas =
+            get_tokens_in_calldata(tx.input(), true).saturating_mul(STANDARD_TOKEN_COST);
+        let factory_deps_hashing_gas = tx.factory_deps_gas_cost();
+
+        self.0.inspector.reset();
+        let result = self.inspect_one_tx(tx)?;
+        let system_contract_gas = self.0.inspector.system_contract_gas();
+
+        let total = result.gas_used();
+        let evm_execution = total
+            .saturating_sub(calldata_gas)
+            .saturating_sub(factory_deps_hashing_gas)
+            .saturating_sub(system_contract_gas);
+        Ok((
+            result,
+            GasProfile {
+                evm_execution,
+                calldata_gas,
+                factory_deps_hashing_gas,
+                system_contract_gas,
+                total,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DefaultZk, ZKsyncTx,
+        api::builder::ZkBuilder,
+        precompiles::l2_base_token::{BASE_COST, L2_BASE_TOKEN_ADDRESS},
+    };
+    use revm::{
+        Context,
+        context::TxEnv,
+        database::{CacheDB, EmptyDB},
+        primitives::{Address, Bytes, TxKind, U256},
+        state::AccountInfo,
+    };
+
+    #[test]
+    fn profile_execution() {
+        let caller = Address::with_last_byte(0x42);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(100)));
+        let mut evm = Context::default()
+            .with_db(db)
+            .build_zk_with_inspector(GasProfiler::default());
+
+        let transfer = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .caller(caller)
+                    .kind(TxKind::Call(Address::with_last_byte(0x43)))
+                    .value(U256::from(1)),
+            )
+            .build_fill();
+        let (result, profile) = evm.profile_execution(transfer).unwrap();
+        assert!(result.is_success());
+        assert_eq!(profile.total, result.gas_used());
+        assert_eq!(profile.system_contract_gas, 0);
+        assert_eq!(profile.calldata_gas, 0);
+
+        // Too short for a selector, the base token reverts charging its base cost.
+        let system_call = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .caller(caller)
+                    .nonce(1)
+                    .kind(TxKind::Call(L2_BASE_TOKEN_ADDRESS))
+                    .data(Bytes::from_static(&[0x01, 0x00])),
+            )
+            .build_fill();
+        let (result, profile) = evm.profile_execution(system_call).unwrap();
+        assert_eq!(profile.total, result.gas_used());
+        assert_eq!(profile.system_contract_gas, BASE_COST);
+        // One non-zero and one zero byte.
+        assert_eq!(profile.calldata_gas, 5 * STANDARD_TOKEN_COST);
+        assert_eq!(profile.evm_execution, 21_000);
+    }
+}
@@ -0,0 +1,207 @@
+//! Replay of historical ZKsync OS blocks.
+use crate::{
+    ZkHaltReason,
+    api::exec::{ZkContextTr, ZkError},
+    evm::ZKsyncEvm,
+    precompiles::l1_messenger::L1_MESSENGER_ADDRESS,
+};
+use revm::{
+    ExecuteEvm,
+    context::ContextSetters,
+    context_interface::result::ExecutionResult,
+    handler::{PrecompileProvider, instructions::EthInstructions},
+    interpreter::{InterpreterResult, interpreter::EthInterpreter},
+    primitives::{B256, Log, keccak256},
+    state::EvmState,
+};
+use std::vec::Vec;
+
+/// Result of [`ZKsyncEvm::replay_block`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockReplayResult {
+    /// Execution results of the transactions, in order.
+    pub tx_results: Vec<ExecutionResult<ZkHaltReason>>,
+    /// Hash of the state changed by the block, see [`state_hash`].
+    pub state_root: B256,
+    /// Logs emitted by the L1 messenger, one per L2 -> L1 message.
+    pub l1_messages: Vec<Log>,
+    /// Gas used by all transactions of the block.
+    pub total_gas_used: u64,
+    /// State changed by the block, not yet committed to the database.
+    pub state: EvmState,
+}
+
+impl<CTX, INSP, PRECOMPILE> ZKsyncEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: ZkContextTr + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    /// Executes the transactions of a block in order on top of the current state.
+    ///
+    /// The journal is finalized at the end and the resulting state is returned without being
+    /// committed to the database. If a transaction returns an error, the state of the whole
+    /// block is discarded and the error is returned.
+    pub fn replay_block<I>(
+        &mut self,
+        block: CTX::Block,
+        txs: I,
+    ) -> Result<BlockReplayResult, ZkError<CTX>>
+    where
+        I: IntoIterator<Item = CTX::Tx>,
+    {
+        self.set_block(block);
+
+        let mut tx_results = Vec::new();
+        let mut l1_messages = Vec::new();
+        let mut total_gas_used = 0u64;
+        for tx in txs {
+            let result = match self.transact_one(tx) {
+                Ok(result) => result,
+                Err(error) => {
+                    // Drop the state of the preceding transactions, the block is not applied.
+                    self.finalize();
+                    return Err(error);
+                }
+            };
+            total_gas_used = total_gas_used.saturating_add(result.gas_used());
+            l1_messages.extend(
+                result
+                    .logs()
+                    .iter()
+                    .filter(|log| log.address == L1_MESSENGER_ADDRESS)
+                    .cloned(),
+            );
+            tx_results.push(result);
+        }
+
+        let state = self.finalize();
+        Ok(BlockReplayResult {
+            tx_results,
+            state_root: state_hash(&state),
+            l1_messages,
+            total_gas_used,
+            state,
+        })
+    }
+}
+
+/// Hashes the state as keccak256 over the touched accounts sorted by address.
+///
+/// Each touched account contributes its address and the keccak256 of its balance, nonce, code
+/// hash and changed storage slots sorted by key. Accounts and slots that were only read are
+/// skipped. This is a commitment to the changed state, not a Merkle root of the full state.
+pub fn state_hash(state: &EvmState) -> B256 {
+    let mut accounts: Vec<_> = state
+        .iter()
+        .filter(|(_, account)| account.is_touched())
+        .collect();
+    accounts.sort_unstable_by_key(|(address, _)| **address);
+
+    let mut preimage = Vec::with_capacity(accounts.len() * 52);
+    for (address, account) in accounts {
+        let mut account_preimage = Vec::new();
+        account_preimage.extend_from_slice(&account.info.balance.to_be_bytes::<32>());
+        account_preimage.extend_from_slice(&account.info.nonce.to_be_bytes());
+        account_preimage.extend_from_slice(account.info.code_hash.as_slice());
+
+        let mut storage: Vec<_> = account
+            .storage
+            .iter()
+            .filter(|(_, slot)| slot.is_changed())
+            .collect();
+        storage.sort_unstable_by_key(|(key, _)| **key);
+        for (key, slot) in storage {
+            account_preimage.extend_from_slice(&key.to_be_bytes::<32>());
+            account_preimage.extend_from_slice(&slot.present_value.to_be_bytes::<32>());
+        }
+
+        preimage.extend_from_slice(address.as_slice());
+        preimage.extend_from_slice(keccak256(&account_preimage).as_slice());
+    }
+    keccak256(&preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::state_hash;
+    use crate::{DefaultZk, ZKsyncTx, api::builder::ZkBuilder};
+    use revm::{
+        Context,
+        context::{BlockEnv, TxEnv},
+        primitives::{Address, U256},
+        state::{Account, AccountInfo, EvmStorageSlot},
+    };
+    use std::vec;
+
+    #[test]
+    fn replay_block_state_root() {
+        let tx0 = ZKsyncTx::builder().build_fill();
+        let tx1 = ZKsyncTx::builder()
+            .base(TxEnv::builder().nonce(1))
+            .build_fill();
+
+        let mut evm = Context::default().build_zk();
+        let one = evm
+            .replay_block(BlockEnv::default(), vec![tx0.clone()])
+            .unwrap();
+        assert_eq!(one.tx_results.len(), 1);
+        assert!(one.tx_results[0].is_success());
+
+        let mut evm = Context::default().build_zk();
+        let two = evm
+            .replay_block(BlockEnv::default(), vec![tx0, tx1])
+            .unwrap();
+        assert_eq!(two.tx_results.len(), 2);
+        assert!(two.tx_results.iter().all(|result| result.is_success()));
+        assert_eq!(two.total_gas_used, 2 * one.total_gas_used);
+        assert_ne!(one.state_root, two.state_root);
+    }
+
+    #[test]
+    fn state_hash_skips_reads() {
+        let mut evm = Context::default().build_zk();
+        let replay = evm
+            .replay_block(BlockEnv::default(), vec![ZKsyncTx::builder().build_fill()])
+            .unwrap();
+
+        // An account and a slot that were only read do not change the hash.
+        let mut state = replay.state.clone();
+        let mut read_only = Account::from(AccountInfo::from_balance(U256::from(1)));
+        read_only
+            .storage
+            .insert(U256::ZERO, EvmStorageSlot::new(U256::from(1), 0));
+        state.insert(Address::with_last_byte(0x42), read_only);
+        assert_eq!(state_hash(&state), replay.state_root);
+
+        // Marking it as touched does.
+        state
+            .get_mut(&Address::with_last_byte(0x42))
+            .unwrap()
+            .mark_touch();
+        assert_ne!(state_hash(&state), replay.state_root);
+    }
+
+    #[test]
+    fn failed_replay_discards_block() {
+        let tx0 = ZKsyncTx::builder().build_fill();
+        let bad_nonce = ZKsyncTx::builder()
+            .base(TxEnv::builder().nonce(5))
+            .build_fill();
+
+        let mut evm = Context::default().build_zk();
+        assert!(
+            evm.replay_block(BlockEnv::default(), vec![tx0.clone(), bad_nonce])
+                .is_err()
+        );
+
+        // The first transaction of the failed block left nothing behind.
+        let replayed = evm
+            .replay_block(BlockEnv::default(), vec![tx0.clone()])
+            .unwrap();
+        let fresh = Context::default()
+            .build_zk()
+            .replay_block(BlockEnv::default(), vec![tx0])
+            .unwrap();
+        assert_eq!(replayed, fresh);
+    }
+}
@@ -0,0 +1,172 @@
+//! Storage overrides for simulating calls on modified state.
+use revm::{
+    Database, DatabaseCommit,
+    primitives::{Address, B256, HashMap, StorageKey, StorageValue},
+    state::{Account, AccountInfo, Bytecode},
+};
+
+/// Database serving overridden storage slots before the wrapped database, e.g. for
+/// `eth_call` with state overrides.
+///
+/// Overrides are only seen when a slot is read from the database, so they neither touch the
+/// journal nor change the gas of the access. Overrides are never committed: slots still
+/// holding their overridden value are dropped from the committed changes, while slots the
+/// transaction wrote are committed like any other write.
+#[derive(Clone, Debug, Default)]
+pub struct StorageOverrideDb<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    overrides: HashMap<Address, HashMap<StorageKey, StorageValue>>,
+}
+
+impl<DB> StorageOverrideDb<DB> {
+    /// Wraps `db` without any overrides.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            overrides: HashMap::default(),
+        }
+    }
+
+    /// Serves `value` for `slot` of `address` instead of the value in the wrapped database.
+    pub fn push_storage_override(
+        &mut self,
+        address: Address,
+        slot: StorageKey,
+        value: StorageValue,
+    ) {
+        self.overrides
+            .entry(address)
+            .or_default()
+            .insert(slot, value);
+    }
+
+    /// Removes all storage overrides.
+    ///
+    /// Slots already loaded into the journal keep their overridden value until it is finalized.
+    pub fn pop_storage_overrides(&mut self) {
+        self.overrides.clear();
+    }
+
+    /// Adds the storage `overrides`, keyed by account and slot.
+    pub fn apply_state_overrides(
+        &mut self,
+        overrides: &HashMap<Address, HashMap<StorageKey, StorageValue>>,
+    ) {
+        for (address, slots) in overrides {
+            for (slot, value) in slots {
+                self.push_storage_override(*address, *slot, *value);
+            }
+        }
+    }
+
+    /// Overridden value of `slot` of `address`, if any.
+    pub fn storage_override(&self, address: Address, slot: StorageKey) -> Option<StorageValue> {
+        self.overrides.get(&address)?.get(&slot).copied()
+    }
+
+    /// Consumes the wrapper and returns the wrapped database.
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+}
+
+impl<DB: Database> Database for StorageOverrideDb<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        match self.storage_override(address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for StorageOverrideDb<DB> {
+    fn commit(&mut self, mut changes: HashMap<Address, Account>) {
+        for (address, slots) in &self.overrides {
+            if let Some(account) = changes.get_mut(address) {
+                account
+                    .storage
+                    .retain(|slot, value| !slots.contains_key(slot) || value.is_changed());
+            }
+        }
+        self.db.commit(changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultZk, ZKsyncTx, api::builder::ZkBuilder};
+    use revm::{
+        Context, ExecuteCommitEvm, ExecuteEvm,
+        context::TxEnv,
+        context_interface::ContextTr,
+        database::{CacheDB, EmptyDB},
+        primitives::{Bytes, TxKind, U256},
+    };
+
+    #[test]
+    fn storage_overrides() {
+        // PUSH1 0x01 SLOAD PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+        let code = Bytecode::new_legacy(Bytes::from_static(&[
+            0x60, 0x01, 0x54, 0x5f, 0x52, 0x60, 0x20, 0x5f, 0xf3,
+        ]));
+        let contract = Address::with_last_byte(0x42);
+        let slot = U256::from(1);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(contract, AccountInfo::default().with_code(code));
+        db.insert_account_storage(contract, slot, U256::from(7))
+            .unwrap();
+        let tx = |nonce| {
+            ZKsyncTx::builder()
+                .base(TxEnv::builder().nonce(nonce).kind(TxKind::Call(contract)))
+                .build_fill()
+        };
+
+        let mut evm = Context::default()
+            .with_db(StorageOverrideDb::new(db))
+            .build_zk();
+        let gas_without_override = evm.transact(tx(0)).unwrap().result.gas_used();
+
+        evm.0
+            .ctx
+            .db_mut()
+            .apply_state_overrides(&HashMap::from_iter([(
+                contract,
+                HashMap::from_iter([(slot, U256::from(1_000))]),
+            )]));
+        let result = evm.transact_commit(tx(0)).unwrap();
+        assert_eq!(
+            U256::from_be_slice(result.output().unwrap()),
+            U256::from(1_000)
+        );
+        // Reading an overridden slot costs the same as reading the stored one.
+        assert_eq!(result.gas_used(), gas_without_override);
+        // The override is not committed, the transaction itself is.
+        let db = &evm.0.ctx.db().db;
+        assert_eq!(db.cache.accounts[&contract].storage[&slot], U256::from(7));
+        assert_eq!(db.cache.accounts[&Address::ZERO].info.nonce, 1);
+
+        evm.0.ctx.db_mut().pop_storage_overrides();
+        let result = evm.transact(tx(1)).unwrap().result;
+        assert_eq!(U256::from_be_slice(result.output().unwrap()), U256::from(7));
+    }
+}
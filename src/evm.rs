@@ -1,9 +1,9 @@
 //! Contains the `[ZKsyncEvm]` type and its implementation of the execution EVM traits.
 use crate::precompiles::ZKsyncPrecompiles;
 use revm::{
-    Database, Inspector,
-    context::{ContextError, ContextSetters, Evm, FrameStack},
-    context_interface::ContextTr,
+    Context, Database, DatabaseCommit, Inspector,
+    context::{ContextError, ContextSetters, Evm, FrameStack, LocalContextTr},
+    context_interface::{Block, ContextTr, JournalTr},
     handler::{
         EthFrame, EvmTr, FrameInitOrResult, ItemOrResult, PrecompileProvider,
         evm::FrameTr,
@@ -11,6 +11,8 @@ use revm::{
     },
     inspector::{InspectorEvmTr, JournalExt},
     interpreter::{InterpreterResult, interpreter::EthInterpreter},
+    primitives::U256,
+    state::EvmState,
 };
 
 /// ZKsync OS EVM extends the [`Evm`] type with specific types and logic.
@@ -58,6 +60,91 @@ impl<CTX, INSP, I, P> ZKsyncEvm<CTX, INSP, I, P> {
     }
 }
 
+impl<BLOCK, TX, CFG, DB, JOURNAL, CHAIN, LOCAL, INSP, I, P>
+    ZKsyncEvm<Context<BLOCK, TX, CFG, DB, JOURNAL, CHAIN, LOCAL>, INSP, I, P>
+where
+    DB: Database,
+    JOURNAL: JournalTr<Database = DB>,
+    LOCAL: LocalContextTr,
+{
+    /// Modifies the transaction in place, keeping the rest of the EVM untouched.
+    pub fn modify_tx<F: FnOnce(&mut TX)>(&mut self, f: F) {
+        f(&mut self.0.ctx.tx);
+    }
+
+    /// Modifies the block in place, keeping the rest of the EVM untouched.
+    pub fn modify_block<F: FnOnce(&mut BLOCK)>(&mut self, f: F) {
+        f(&mut self.0.ctx.block);
+    }
+
+    /// Discards the uncommitted changes of the journal.
+    pub fn reset_journal(&mut self) {
+        self.0.ctx.journaled_state.discard_tx();
+    }
+
+    /// Discards the uncommitted journal changes, the per-transaction data and the transaction,
+    /// keeping the database.
+    ///
+    /// The block, the configuration, the inspector and the precompiles are left untouched.
+    pub fn reset_preserving_db(&mut self)
+    where
+        TX: Default,
+    {
+        self.0.ctx.journaled_state.discard_tx();
+        self.0.ctx.journaled_state.clear();
+        self.0.ctx.local.clear();
+        self.0.ctx.tx = TX::default();
+    }
+
+    /// Same as [`Self::reset_preserving_db`], but also replaces the database with `db`, e.g.
+    /// the one the EVM was constructed with.
+    pub fn reset_with_db(&mut self, db: DB)
+    where
+        TX: Default,
+    {
+        self.reset_preserving_db();
+        *self.0.ctx.journaled_state.db_mut() = db;
+    }
+
+    /// Executes the following transactions in the given block, e.g. for `eth_call` at a
+    /// historical block.
+    ///
+    /// Uncommitted journal changes and per-transaction data are discarded.
+    pub fn with_block_override(&mut self, block: BLOCK) -> &mut Self {
+        self.0.ctx.block = block;
+        self.0.ctx.journaled_state.discard_tx();
+        self.0.ctx.local.clear();
+        self
+    }
+
+    /// Number of the current block.
+    pub fn current_block_number(&self) -> U256
+    where
+        BLOCK: Block,
+    {
+        self.0.ctx.block.number()
+    }
+
+    /// Timestamp of the current block.
+    pub fn current_timestamp(&self) -> U256
+    where
+        BLOCK: Block,
+    {
+        self.0.ctx.block.timestamp()
+    }
+
+    /// Writes a state diff produced by another EVM directly to the database, bypassing
+    /// the journal.
+    ///
+    /// Used by nodes that sync state from the sequencer without re-executing transactions.
+    pub fn apply_state_diff(&mut self, diff: EvmState)
+    where
+        DB: DatabaseCommit,
+    {
+        self.0.ctx.journaled_state.db_mut().commit(diff);
+    }
+}
+
 impl<CTX, INSP, I, P> InspectorEvmTr for ZKsyncEvm<CTX, INSP, I, P>
 where
     CTX: ContextTr<Journal: JournalExt> + ContextSetters,
@@ -164,3 +251,91 @@ where
         self.0.frame_return_result(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefaultZk, ZKsyncTx, api::builder::ZkBuilder};
+    use revm::{
+        Context, ExecuteCommitEvm, ExecuteEvm,
+        context::{BlockEnv, TxEnv},
+        database::{CacheDB, EmptyDB},
+        primitives::{Address, Bytes, TxKind, U256},
+        state::{AccountInfo, Bytecode},
+    };
+
+    #[test]
+    fn apply_state_diff() {
+        let mut sequencer = Context::default()
+            .with_db(CacheDB::<EmptyDB>::default())
+            .build_zk();
+        let diff = sequencer
+            .transact(ZKsyncTx::builder().build_fill())
+            .unwrap()
+            .state;
+
+        let mut node = Context::default()
+            .with_db(CacheDB::<EmptyDB>::default())
+            .build_zk();
+        node.apply_state_diff(diff);
+        assert_eq!(
+            node.0.ctx.journaled_state.db().cache.accounts[&Address::ZERO]
+                .info
+                .nonce,
+            1
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let funded = Address::with_last_byte(0x42);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(funded, AccountInfo::from_balance(U256::from(1)));
+        let mut evm = Context::default().with_db(db.clone()).build_zk();
+        let tx = |nonce: u64| {
+            ZKsyncTx::builder()
+                .base(TxEnv::builder().nonce(nonce))
+                .build_fill()
+        };
+        assert!(evm.transact_commit(tx(0)).unwrap().is_success());
+        assert!(evm.transact(tx(1)).is_ok());
+
+        // The committed nonce survives, the uncommitted one does not.
+        evm.reset_preserving_db();
+        assert_eq!(evm.0.ctx.tx, ZKsyncTx::default());
+        assert!(evm.transact_commit(tx(1)).unwrap().is_success());
+
+        evm.reset_with_db(db);
+        let accounts = &evm.0.ctx.journaled_state.db().cache.accounts;
+        assert!(!accounts.contains_key(&Address::ZERO));
+        assert_eq!(accounts[&funded].info.balance, U256::from(1));
+        assert!(evm.transact(tx(0)).is_ok());
+    }
+
+    #[test]
+    fn block_override() {
+        // TIMESTAMP PUSH0 MSTORE PUSH1 0x20 PUSH0 RETURN
+        let code = Bytecode::new_legacy(Bytes::from_static(&[
+            0x42, 0x5f, 0x52, 0x60, 0x20, 0x5f, 0xf3,
+        ]));
+        let contract = Address::with_last_byte(0x42);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(contract, AccountInfo::default().with_code(code));
+        let mut evm = Context::default().with_db(db).build_zk();
+
+        for timestamp in [1_000u64, 2_000] {
+            evm.with_block_override(BlockEnv {
+                timestamp: U256::from(timestamp),
+                ..Default::default()
+            });
+            assert_eq!(evm.current_timestamp(), U256::from(timestamp));
+            let tx = ZKsyncTx::builder()
+                .base(TxEnv::builder().kind(TxKind::Call(contract)))
+                .build_fill();
+            let result = evm.transact(tx).unwrap().result;
+            assert_eq!(
+                U256::from_be_slice(result.output().unwrap()),
+                U256::from(timestamp)
+            );
+        }
+    }
+}
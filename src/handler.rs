@@ -2,9 +2,10 @@
 use std::boxed::Box;
 
 use crate::{
-    ZkHaltReason,
+    ExecutionMode, ZkHaltReason, ZkSpecId,
     api::exec::ZkContextTr,
-    transaction::{ZKsyncTxError, ZkTxTr},
+    precompiles::BOOTLOADER_FORMAL_ADDRESS,
+    transaction::{ZKsyncTxError, ZkTxTr, abstraction::EIP712_TRANSACTION_TYPE},
 };
 use revm::{
     context::{LocalContextTr, result::InvalidTransaction},
@@ -26,8 +27,30 @@ use revm::{
         interpreter::EthInterpreter, interpreter_action::FrameInit,
     },
     primitives::U256,
+    state::Bytecode,
 };
 
+/// Maximum number of pubdata bytes a single batch can publish.
+pub const MAX_PUBDATA_PER_BATCH: u64 = 120_000;
+
+/// Minimum gas limit of a transaction: the intrinsic gas of its calldata and the gas needed
+/// to hash its factory deps.
+///
+/// Does not include the EIP-7623 floor or the gas spent on execution.
+pub fn minimum_gas_limit(tx: &impl ZkTxTr, spec: ZkSpecId) -> u64 {
+    spec.gas_constants()
+        .intrinsic_gas(tx.input())
+        .saturating_add(tx.factory_deps_gas_cost())
+}
+
+/// Returns `true` if the transaction is an L2 system call sent by the bootloader.
+///
+/// The system call flag of transactions from other callers is ignored. L1 -> L2 transactions
+/// are never treated as system calls, their mint and refund must always be applied.
+fn is_bootloader_system_call(tx: &impl ZkTxTr) -> bool {
+    !tx.is_l1_to_l2_tx() && tx.is_system_call() && tx.caller() == BOOTLOADER_FORMAL_ADDRESS
+}
+
 /// ZKsync OS handler extends the [`Handler`] with ZKsync OS specific logic.
 #[derive(Debug, Clone)]
 pub struct ZKsyncHandler<EVM, ERROR, FRAME> {
@@ -86,10 +109,66 @@ where
             return Ok(());
         }
 
+        if tx.reserved_dynamic().is_some() && tx.tx_type() != EIP712_TRANSACTION_TYPE {
+            return Err(ZKsyncTxError::UnsupportedTxField("reserved_dynamic".into()).into());
+        }
+
+        let spec = ctx.cfg().spec();
+        let minimum = minimum_gas_limit(tx, spec);
+        if tx.gas_limit() < minimum {
+            return Err(ZKsyncTxError::GasLimitTooLow {
+                provided: tx.gas_limit(),
+                minimum,
+            }
+            .into());
+        }
+
+        // Factory deps are published as pubdata, they must fit into a single batch.
+        // Not enforced while estimating, so the estimate is returned instead of an error.
+        let pubdata = tx.factory_deps_total_bytes() as u64;
+        if pubdata > MAX_PUBDATA_PER_BATCH && ctx.execution_mode() != ExecutionMode::Estimate {
+            return Err(ZKsyncTxError::WouldExceedBatchPubdataLimit {
+                required: pubdata,
+                limit: MAX_PUBDATA_PER_BATCH,
+            }
+            .into());
+        }
+
         // Do not perform any extra validation for L1 -> L2 transactions, they are pre-verified on L1.
         self.mainnet.validate_env(evm)
     }
 
+    fn validate_initial_tx_gas(&self, evm: &Self::Evm) -> Result<InitialAndFloorGas, Self::Error> {
+        let mut init_and_floor_gas = self.mainnet.validate_initial_tx_gas(evm)?;
+
+        // Factory deps are hashed before execution, charge it as part of the initial gas.
+        let tx = evm.ctx_ref().tx();
+        let factory_deps_gas = tx.factory_deps_gas_cost();
+        if factory_deps_gas > 0 {
+            init_and_floor_gas.initial_gas = init_and_floor_gas
+                .initial_gas
+                .saturating_add(factory_deps_gas);
+            if init_and_floor_gas.initial_gas > tx.gas_limit() {
+                return Err(ZKsyncTxError::InsufficientGasForFactoryDeps {
+                    required: init_and_floor_gas.initial_gas,
+                    available: tx.gas_limit(),
+                }
+                .into());
+            }
+        }
+        Ok(init_and_floor_gas)
+    }
+
+    fn pre_execution(&self, evm: &mut Self::Evm) -> Result<u64, Self::Error> {
+        // Factory deps must be retrievable by hash during execution.
+        let ctx = evm.ctx();
+        let factory_deps = ctx.tx().factory_deps().to_vec();
+        for dep in factory_deps {
+            ctx.save_bytecode(Bytecode::new_legacy(dep));
+        }
+        self.mainnet.pre_execution(evm)
+    }
+
     #[inline]
     fn post_execution(
         &self,
@@ -98,16 +177,8 @@ where
         init_and_floor_gas: InitialAndFloorGas,
         eip7702_gas_refund: i64,
     ) -> Result<(), Self::Error> {
-        if let Some(gas_used_override) = evm.ctx().tx().gas_used_override() {
-            let gas_limit = evm.ctx().tx().gas_limit();
-            // Just in case use at most `gas_limit` gas to prevent the underflow
-            let used = gas_used_override.min(gas_limit);
-            let unused = gas_limit - used;
-
-            // Rewrite the Gas object to match ZKsync OS usage.
-            let gas = exec_result.gas_mut();
-            *gas = Gas::new_spent(gas_limit);
-            gas.erase_cost(unused);
+        if evm.ctx().tx().gas_used_override().is_some() {
+            // The Gas object already matches ZKsync OS usage, see `last_frame_result`.
             // IMPORTANT: ignore EVM-native refunds: (do NOT call `gas.record_refund(...)` here)
             //    self.refund(evm, exec_result, eip7702_gas_refund);  // <-- intentionally NOT called
 
@@ -130,12 +201,13 @@ where
         evm: &mut Self::Evm,
     ) -> Result<(), Self::Error> {
         let ctx = evm.ctx();
-
-        let basefee = ctx.block().basefee() as u128;
-        let blob_price = ctx.block().blob_gasprice().unwrap_or_default();
+        let basefee = ctx.current_base_fee() as u128;
+        let blob_price = ctx.current_blob_gas_price();
         let is_l1_to_l2_tx = ctx.tx().is_l1_to_l2_tx();
         let is_eip3607_disabled = ctx.cfg().is_eip3607_disabled();
-        let is_nonce_check_disabled = ctx.cfg().is_nonce_check_disabled();
+        // Replays may force the nonce of the caller, see `ZkContextTr::set_forced_nonce`.
+        let is_nonce_check_disabled = ctx.cfg().is_nonce_check_disabled()
+            || ctx.forced_nonce(ctx.tx().caller()) == Some(ctx.tx().nonce());
 
         let mint = ctx.tx().mint().unwrap_or_default();
 
@@ -153,11 +225,25 @@ where
             )?;
         }
 
+        // System calls from the bootloader are neither charged nor bump its nonce.
+        if is_bootloader_system_call(tx) {
+            return Ok(());
+        }
+
         // old balance is journaled before mint is incremented.
         let old_balance = caller_account.info.balance;
 
         let mut new_balance = caller_account.info.balance.saturating_add(U256::from(mint));
 
+        // The value of a deposit is paid from the minted amount, it can never exceed it.
+        if is_l1_to_l2_tx && tx.value() > mint {
+            return Err(ZKsyncTxError::MintInsufficientForValue {
+                mint,
+                value: tx.value(),
+            }
+            .into());
+        }
+
         let max_balance_spending = tx.max_balance_spending()?;
 
         if !is_l1_to_l2_tx && max_balance_spending > new_balance {
@@ -200,6 +286,10 @@ where
         evm: &mut Self::Evm,
         frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
     ) -> Result<(), Self::Error> {
+        // The bootloader was not charged, see `validate_against_state_and_deduct_caller`.
+        if is_bootloader_system_call(evm.ctx().tx()) {
+            return Ok(());
+        }
         reimburse_caller(evm.ctx(), frame_result.gas(), U256::ZERO)?;
 
         let is_l1_to_l2_tx = evm.ctx().tx().is_l1_to_l2_tx();
@@ -211,8 +301,7 @@ where
                 .refund_recipient()
                 .expect("Refund recipient is missing for L1 -> L2 tx");
 
-            let basefee = evm.ctx().block().basefee() as u128;
-            let effective_gas_price = evm.ctx().tx().effective_gas_price(basefee);
+            let effective_gas_price = evm.ctx().current_effective_gas_price();
             let spent_fee =
                 U256::from(frame_result.gas().spent()) * U256::from(effective_gas_price);
             let mint = evm.ctx().tx().mint().unwrap_or_default();
@@ -240,9 +329,14 @@ where
         evm: &mut Self::Evm,
         frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
     ) -> Result<(), Self::Error> {
+        // The operator is not paid for estimations and bootloader system calls.
+        if evm.ctx().execution_mode() == ExecutionMode::Estimate
+            || is_bootloader_system_call(evm.ctx().tx())
+        {
+            return Ok(());
+        }
         let beneficiary = evm.ctx().block().beneficiary();
-        let basefee = evm.ctx().block().basefee() as u128;
-        let effective_gas_price = evm.ctx().tx().effective_gas_price(basefee);
+        let effective_gas_price = evm.ctx().current_effective_gas_price();
 
         // reward beneficiary
         evm.ctx().journal_mut().balance_incr(
@@ -253,6 +347,27 @@ where
         Ok(())
     }
 
+    fn last_frame_result(
+        &mut self,
+        evm: &mut Self::Evm,
+        frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        // With `gas_used_override` the gas usage is dictated by the original ZKsync OS execution,
+        // so the remaining gas of the EVM execution must not be handed back to the caller here.
+        // This is the only place the Gas object is rewritten, for both regular and forced-fail
+        // executions.
+        if let Some(gas_used_override) = evm.ctx().tx().gas_used_override() {
+            let gas_limit = evm.ctx().tx().gas_limit();
+            // Just in case use at most `gas_limit` gas to prevent the underflow
+            let used = gas_used_override.min(gas_limit);
+            let gas = frame_result.gas_mut();
+            *gas = Gas::new_spent(gas_limit);
+            gas.erase_cost(gas_limit - used);
+            return Ok(());
+        }
+        self.mainnet.last_frame_result(evm, frame_result)
+    }
+
     fn execution_result(
         &mut self,
         evm: &mut Self::Evm,
@@ -285,6 +400,7 @@ where
         let mut exec_result = if evm.ctx().tx().force_fail() {
             // Synthesize a top-level REVERT frame result (no state changes).
             // 1) Make an InterpreterResult with REVERT + returndata.
+            // No gas remains, so without `gas_used_override` the whole gas limit is spent.
             let ir = InterpreterResult::new(
                 InstructionResult::Revert,
                 Default::default(),
@@ -293,18 +409,8 @@ where
             // 2) Wrap it as a CallOutcome; memory range is irrelevant here.
             let mut fr = FrameResult::Call(CallOutcome::new(ir, 0..0));
 
-            let gas_limit = evm.ctx().tx().gas_limit();
-            let gas_used = evm.ctx().tx().gas_used_override().unwrap_or(gas_limit);
-
-            // 3) Set gas to match your ZK usage now (limit – unused).
-            let used = gas_used.min(gas_limit);
-            let unused = gas_limit - used;
-            let gas = fr.gas_mut();
-            *gas = Gas::new_spent(gas_limit);
-            gas.erase_cost(unused);
-
-            // Ensure gas object is initialized the same way a normal top-level return would do.
-            // last_frame_result() sets `Gas::new_spent(gas_limit)` and handles "remaining" & refund flags.
+            // 3) Initialize the gas object the same way a normal top-level return would do,
+            //    this is where `gas_used_override` is applied.
             self.last_frame_result(evm, &mut fr)?;
 
             fr
@@ -328,3 +434,306 @@ where
 {
     type IT = EthInterpreter;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultZk, ZKsyncTx, api::builder::ZkBuilder};
+    use revm::{
+        Context, ExecuteCommitEvm, ExecuteEvm,
+        context::TxEnv,
+        database::{CacheDB, EmptyDB},
+        interpreter::gas::{KECCAK256, KECCAK256WORD},
+        primitives::{Address, Bytes, TxKind},
+        state::AccountInfo,
+    };
+    use std::{vec, vec::Vec};
+
+    #[test]
+    fn factory_deps_must_fit_into_batch() {
+        let tx = |len: u64| {
+            ZKsyncTx::builder()
+                .factory_deps(vec![Bytes::from(vec![0; len as usize])])
+                .build_fill()
+        };
+
+        let mut evm = Context::default().build_zk();
+        assert!(evm.transact(tx(MAX_PUBDATA_PER_BATCH)).is_ok());
+        assert_eq!(
+            evm.transact(tx(MAX_PUBDATA_PER_BATCH + 1)).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::WouldExceedBatchPubdataLimit {
+                required: MAX_PUBDATA_PER_BATCH + 1,
+                limit: MAX_PUBDATA_PER_BATCH,
+            })
+        );
+    }
+
+    #[test]
+    fn gas_limit_too_low() {
+        let tx = |gas_limit: u64, factory_deps: Vec<Bytes>| {
+            ZKsyncTx::builder()
+                .base(TxEnv::builder().gas_limit(gas_limit))
+                .factory_deps(factory_deps)
+                .build_fill()
+        };
+
+        let mut evm = Context::default().build_zk();
+        assert!(evm.transact(tx(21_000, vec![])).is_ok());
+        assert_eq!(
+            evm.transact(tx(20_999, vec![])).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::GasLimitTooLow {
+                provided: 20_999,
+                minimum: 21_000,
+            })
+        );
+
+        let deps = vec![Bytes::from(vec![0; 64])];
+        let minimum = minimum_gas_limit(&tx(0, deps.clone()), ZkSpecId::Atlas);
+        assert_eq!(minimum, 21_000 + KECCAK256 + 2 * KECCAK256WORD);
+        assert!(evm.transact(tx(minimum, deps.clone())).is_ok());
+        assert_eq!(
+            evm.transact(tx(minimum - 1, deps.clone())).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::GasLimitTooLow {
+                provided: minimum - 1,
+                minimum,
+            })
+        );
+        assert_eq!(
+            evm.transact(tx(20_999, deps)).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::GasLimitTooLow {
+                provided: 20_999,
+                minimum,
+            })
+        );
+    }
+
+    #[test]
+    fn estimate_mode() {
+        let caller = Address::with_last_byte(0x42);
+        let beneficiary = Address::with_last_byte(0xbe);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        let tx = |nonce: u64, len: usize| {
+            ZKsyncTx::builder()
+                .base(
+                    TxEnv::builder()
+                        .caller(caller)
+                        .nonce(nonce)
+                        .gas_limit(100_000)
+                        .gas_price(1),
+                )
+                .factory_deps(vec![Bytes::from(vec![0; len])])
+                .build_fill()
+        };
+
+        let mut evm = Context::default().with_db(db).build_zk();
+        evm.modify_block(|block| block.beneficiary = beneficiary);
+        let output = evm.transact(tx(0, 0)).unwrap();
+        assert!(!output.state[&beneficiary].info.balance.is_zero());
+
+        evm.0.ctx.set_execution_mode(ExecutionMode::Estimate);
+        let output = evm.transact(tx(0, 0)).unwrap();
+        assert!(
+            output
+                .state
+                .get(&beneficiary)
+                .is_none_or(|account| account.info.balance.is_zero())
+        );
+        // The mode is reset after every transaction.
+        assert_eq!(evm.0.ctx.execution_mode(), ExecutionMode::Execute);
+
+        let oversized = MAX_PUBDATA_PER_BATCH as usize + 1;
+        assert!(evm.transact(tx(0, oversized)).is_err());
+        evm.0.ctx.set_execution_mode(ExecutionMode::Estimate);
+        assert!(evm.transact(tx(0, oversized)).is_ok());
+    }
+
+    #[test]
+    fn bootloader_system_calls_are_not_charged() {
+        let user = Address::with_last_byte(0x42);
+        let target = Address::with_last_byte(0x43);
+        let balance = U256::from(1_000_000);
+        let mut db = CacheDB::<EmptyDB>::default();
+        for caller in [BOOTLOADER_FORMAL_ADDRESS, user] {
+            db.insert_account_info(caller, AccountInfo::from_balance(balance));
+        }
+        let tx = |caller: Address, nonce: u64, is_system: bool| {
+            ZKsyncTx::builder()
+                .base(
+                    TxEnv::builder()
+                        .caller(caller)
+                        .kind(TxKind::Call(target))
+                        .nonce(nonce)
+                        .gas_limit(100_000)
+                        .gas_price(1),
+                )
+                .mark_as_system_call(is_system)
+                .build_fill()
+        };
+        let mut evm = Context::default().with_db(db).build_zk();
+
+        // A system call from the bootloader is free and keeps its nonce.
+        let result = evm
+            .transact_commit(tx(BOOTLOADER_FORMAL_ADDRESS, 0, true))
+            .unwrap();
+        assert!(result.is_success());
+        let accounts = &evm.0.ctx.journaled_state.db().cache.accounts;
+        let bootloader = &accounts[&BOOTLOADER_FORMAL_ADDRESS].info;
+        assert_eq!(bootloader.balance, balance);
+        assert_eq!(bootloader.nonce, 0);
+
+        // A regular call from the bootloader is charged like any other.
+        let result = evm
+            .transact_commit(tx(BOOTLOADER_FORMAL_ADDRESS, 0, false))
+            .unwrap();
+        let accounts = &evm.0.ctx.journaled_state.db().cache.accounts;
+        let bootloader = &accounts[&BOOTLOADER_FORMAL_ADDRESS].info;
+        assert_eq!(bootloader.balance, balance - U256::from(result.gas_used()));
+        assert_eq!(bootloader.nonce, 1);
+
+        // Users cannot opt out of the charges with the system call flag.
+        let result = evm.transact_commit(tx(user, 0, true)).unwrap();
+        assert!(result.is_success());
+        let user = &evm.0.ctx.journaled_state.db().cache.accounts[&user].info;
+        assert_eq!(user.balance, balance - U256::from(result.gas_used()));
+        assert_eq!(user.nonce, 1);
+
+        // The caller is still validated: the nonce must match and the bootloader has no code.
+        assert_eq!(
+            evm.transact(tx(BOOTLOADER_FORMAL_ADDRESS, 0, true))
+                .unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::NonceTooLow { tx: 0, state: 1 }.into())
+        );
+        let code = Bytecode::new_legacy(Bytes::from_static(&[0x00]));
+        evm.0
+            .ctx
+            .journaled_state
+            .db_mut()
+            .insert_account_info(BOOTLOADER_FORMAL_ADDRESS, AccountInfo::from_bytecode(code));
+        assert_eq!(
+            evm.transact(tx(BOOTLOADER_FORMAL_ADDRESS, 0, true))
+                .unwrap_err(),
+            EVMError::Transaction(InvalidTransaction::RejectCallerWithCode.into())
+        );
+    }
+
+    #[test]
+    fn mint_must_cover_value() {
+        let tx = |mint: u64, value: u64| {
+            ZKsyncTx::builder()
+                .base(
+                    TxEnv::builder()
+                        .caller(Address::with_last_byte(0x42))
+                        .kind(TxKind::Call(Address::with_last_byte(0x43)))
+                        .value(U256::from(value))
+                        .gas_limit(100_000),
+                )
+                .mint(U256::from(mint))
+                .refund_recipient(Some(Address::with_last_byte(0x42)))
+                .apply_l1_to_l2_defaults(0)
+                .build_fill()
+        };
+
+        let mut evm = Context::default().build_zk();
+        assert!(evm.transact(tx(1_000, 1_000)).unwrap().result.is_success());
+        assert!(evm.transact(tx(0, 0)).unwrap().result.is_success());
+        assert_eq!(
+            evm.transact(tx(1_000, 1_001)).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::MintInsufficientForValue {
+                mint: U256::from(1_000),
+                value: U256::from(1_001),
+            })
+        );
+    }
+
+    #[test]
+    fn l1_to_l2_txs_from_bootloader_are_minted() {
+        let refund_recipient = Address::with_last_byte(0x42);
+        let mint = U256::from(1_000_000);
+        let tx = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .caller(BOOTLOADER_FORMAL_ADDRESS)
+                    .kind(TxKind::Call(Address::with_last_byte(0x43)))
+                    .gas_limit(100_000),
+            )
+            .mint(mint)
+            .refund_recipient(Some(refund_recipient))
+            .mark_as_system_call(true)
+            .apply_l1_to_l2_defaults(0)
+            .build_fill();
+
+        let mut evm = Context::default().build_zk();
+        let output = evm.transact(tx).unwrap();
+        assert!(output.result.is_success());
+        // The system call flag does not skip the mint and the refund of L1 -> L2 transactions.
+        assert_eq!(output.state[&refund_recipient].info.balance, mint);
+    }
+
+    #[test]
+    fn forced_nonce() {
+        let caller = Address::with_last_byte(0x42);
+        let tx = ZKsyncTx::builder()
+            .base(TxEnv::builder().caller(caller).nonce(5))
+            .build_fill();
+
+        let mut evm = Context::default().build_zk();
+        assert!(matches!(
+            evm.transact(tx.clone()),
+            Err(EVMError::Transaction(ZKsyncTxError::Base(
+                InvalidTransaction::NonceTooHigh { tx: 5, state: 0 }
+            )))
+        ));
+
+        evm.0.ctx.set_forced_nonce(caller, 5);
+        assert!(evm.transact(tx.clone()).is_ok());
+        // Forced nonces are cleared after every transaction.
+        assert_eq!(evm.0.ctx.forced_nonce(caller), None);
+
+        evm.0.ctx.set_forced_nonce(caller, 5);
+        evm.0.ctx.clear_forced_nonce(caller);
+        assert!(evm.transact(tx).is_err());
+    }
+
+    #[test]
+    fn force_fail_spends_gas_used_override() {
+        let caller = Address::with_last_byte(0x42);
+        let balance = U256::from(1_000_000);
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(balance));
+        let tx = |force_fail: bool, gas_used_override: Option<u64>| {
+            ZKsyncTx::builder()
+                .base(
+                    TxEnv::builder()
+                        .caller(caller)
+                        .gas_limit(100_000)
+                        .gas_price(1),
+                )
+                .force_fail(force_fail)
+                .gas_used_override(gas_used_override)
+                .build_fill()
+        };
+        let mut evm = Context::default().with_db(db).build_zk();
+
+        let output = evm.transact(tx(true, Some(50_000))).unwrap();
+        assert!(!output.result.is_success());
+        assert_eq!(output.result.gas_used(), 50_000);
+        assert_eq!(
+            output.state[&caller].info.balance,
+            balance - U256::from(50_000)
+        );
+
+        // The override applies to regular executions the same way.
+        let output = evm.transact(tx(false, Some(50_000))).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(output.result.gas_used(), 50_000);
+
+        // Without an override a forced failure spends the whole gas limit.
+        let output = evm.transact(tx(true, None)).unwrap();
+        assert_eq!(output.result.gas_used(), 100_000);
+        assert_eq!(
+            output.state[&caller].info.balance,
+            balance - U256::from(100_000)
+        );
+    }
+}
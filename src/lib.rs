@@ -4,6 +4,7 @@
 pub mod api;
 pub mod evm;
 pub mod handler;
+pub mod local;
 pub mod precompiles;
 pub mod result;
 pub mod spec;
@@ -14,6 +15,7 @@ pub use api::{
     default_ctx::{DefaultZk, ZkContext},
 };
 pub use evm::ZKsyncEvm;
+pub use local::{ExecutionMode, ZkLocalContext};
 pub use result::ZkHaltReason;
 pub use spec::*;
 pub use transaction::{ZKsyncTx, error::ZKsyncTxError};
@@ -0,0 +1,114 @@
+//! ZKsync OS local context that is cleared after every transaction.
+use core::cell::RefCell;
+use revm::{
+    context::LocalContextTr,
+    primitives::{Address, B256, HashMap},
+    state::Bytecode,
+};
+use std::{rc::Rc, vec::Vec};
+
+/// Mode the ZKsync OS EVM runs the current transaction in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExecutionMode {
+    /// Regular execution.
+    #[default]
+    Execute,
+    /// Gas estimation. Operator fees are not charged and batch pubdata limits are not
+    /// enforced.
+    Estimate,
+    /// Read only call. State modifying system contract calls revert.
+    StaticCall,
+}
+
+/// Local context used by the ZKsync OS EVM.
+///
+/// Besides the shared memory buffer required by the interpreter, it holds ZKsync OS
+/// per-transaction accounting. Everything is reset in [`LocalContextTr::clear`].
+#[derive(Clone, Debug)]
+pub struct ZkLocalContext {
+    /// Interpreter shared memory buffer. A reused memory buffer for calls.
+    pub shared_memory_buffer: Rc<RefCell<Vec<u8>>>,
+    /// Number of pubdata bytes written by the current transaction.
+    ///
+    /// Not reverted together with call frames, so it is an upper bound.
+    pub pubdata_written: u64,
+    /// Bytecodes made available to the current transaction, keyed by their hash.
+    ///
+    /// Filled from the transaction factory deps before execution.
+    pub bytecodes: HashMap<B256, Bytecode>,
+    /// Hash of the current transaction, if known.
+    pub transaction_hash: Option<B256>,
+    /// Mode the current transaction is executed in.
+    pub execution_mode: ExecutionMode,
+    /// Nonces accepted for the given callers regardless of their account nonce.
+    pub forced_nonces: HashMap<Address, u64>,
+    /// Hashes of the L2 -> L1 messages sent by the current transaction, in order.
+    ///
+    /// Not reverted together with call frames, like [`Self::pubdata_written`].
+    pub l2_to_l1_messages: Vec<B256>,
+}
+
+impl Default for ZkLocalContext {
+    fn default() -> Self {
+        Self {
+            shared_memory_buffer: Rc::new(RefCell::new(Vec::with_capacity(1024 * 4))),
+            pubdata_written: 0,
+            bytecodes: HashMap::default(),
+            transaction_hash: None,
+            execution_mode: ExecutionMode::Execute,
+            forced_nonces: HashMap::default(),
+            l2_to_l1_messages: Vec::new(),
+        }
+    }
+}
+
+impl ZkLocalContext {
+    /// Creates a new local context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LocalContextTr for ZkLocalContext {
+    fn shared_memory_buffer(&self) -> &Rc<RefCell<Vec<u8>>> {
+        &self.shared_memory_buffer
+    }
+
+    fn clear(&mut self) {
+        self.shared_memory_buffer.borrow_mut().clear();
+        self.pubdata_written = 0;
+        self.bytecodes.clear();
+        self.transaction_hash = None;
+        self.execution_mode = ExecutionMode::Execute;
+        self.forced_nonces.clear();
+        self.l2_to_l1_messages.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_resets_pubdata() {
+        let mut local = ZkLocalContext::new();
+        local.pubdata_written = 100;
+        local.bytecodes.insert(B256::ZERO, Bytecode::default());
+        local.transaction_hash = Some(B256::ZERO);
+        local.execution_mode = ExecutionMode::StaticCall;
+        local.forced_nonces.insert(Address::ZERO, 1);
+        local.l2_to_l1_messages.push(B256::ZERO);
+        local
+            .shared_memory_buffer
+            .borrow_mut()
+            .extend_from_slice(&[1, 2, 3]);
+        local.clear();
+        assert_eq!(local.pubdata_written, 0);
+        assert!(local.bytecodes.is_empty());
+        assert!(local.transaction_hash.is_none());
+        assert_eq!(local.execution_mode, ExecutionMode::Execute);
+        assert!(local.forced_nonces.is_empty());
+        assert!(local.l2_to_l1_messages.is_empty());
+        assert!(local.shared_memory_buffer.borrow().is_empty());
+    }
+}
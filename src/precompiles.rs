@@ -1,16 +1,19 @@
 //! Contains ZKsync OS specific precompiles.
-use crate::ZkSpecId;
+use crate::{ExecutionMode, ZkSpecId, api::exec::ZkContextTr};
+use core::fmt::Write;
 use revm::{
-    context::{Cfg, LocalContextTr},
-    context_interface::ContextTr,
+    context::{Cfg, ContextError, JournalTr, LocalContextTr},
     handler::{EthPrecompiles, PrecompileProvider},
-    interpreter::{InputsImpl, InterpreterResult},
-    precompile::{Precompiles, bn254, hash, identity, modexp, secp256k1},
-    primitives::{Address, OnceLock},
+    interpreter::{CallInput, Gas, InputsImpl, InstructionResult, InterpreterResult},
+    precompile::{Precompile, PrecompileId, Precompiles, bn254, hash, identity, modexp, secp256k1},
+    primitives::{Address, B256, Bytes, HashMap, OnceLock, address, keccak256},
 };
 use std::boxed::Box;
-use std::string::String;
+use std::format;
+use std::string::{String, ToString};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::vec;
+use std::vec::Vec;
 pub mod deployer;
 pub mod l1_messenger;
 pub mod l2_base_token;
@@ -19,91 +22,451 @@ use deployer::{CONTRACT_DEPLOYER_ADDRESS, deployer_precompile_call};
 use l1_messenger::{L1_MESSENGER_ADDRESS, l1_messenger_precompile_call};
 use l2_base_token::{L2_BASE_TOKEN_ADDRESS, l2_base_token_precompile_call};
 
+/// Formal address of the bootloader, the caller of system transactions.
+pub const BOOTLOADER_FORMAL_ADDRESS: Address = address!("0000000000000000000000000000000000008001");
+
+/// First address of the range reserved for ZKsync OS system contracts.
+pub const SYSTEM_CONTRACTS_START: Address = address!("0000000000000000000000000000000000008001");
+/// Last address of the range reserved for ZKsync OS system contracts.
+pub const SYSTEM_CONTRACTS_END: Address = address!("000000000000000000000000000000000000800f");
+
+/// System contracts implemented natively by [`ZKsyncPrecompiles`].
+pub const ZKSYNC_SYSTEM_CONTRACTS: [Address; 3] = [
+    CONTRACT_DEPLOYER_ADDRESS,
+    L1_MESSENGER_ADDRESS,
+    L2_BASE_TOKEN_ADDRESS,
+];
+
+/// Human readable names of the precompiles and system contracts, used in debugging output.
+pub const PRECOMPILE_NAMES: &[(Address, &str)] = &[
+    (
+        address!("0000000000000000000000000000000000000001"),
+        "ECRecover",
+    ),
+    (
+        address!("0000000000000000000000000000000000000002"),
+        "SHA256",
+    ),
+    (
+        address!("0000000000000000000000000000000000000003"),
+        "RIPEMD160",
+    ),
+    (
+        address!("0000000000000000000000000000000000000004"),
+        "Identity",
+    ),
+    (
+        address!("0000000000000000000000000000000000000005"),
+        "ModExp",
+    ),
+    (
+        address!("0000000000000000000000000000000000000006"),
+        "ECAdd",
+    ),
+    (
+        address!("0000000000000000000000000000000000000007"),
+        "ECMul",
+    ),
+    (
+        address!("0000000000000000000000000000000000000008"),
+        "ECPairing",
+    ),
+    (CONTRACT_DEPLOYER_ADDRESS, "ContractDeployer"),
+    (L1_MESSENGER_ADDRESS, "L1Messenger"),
+    (L2_BASE_TOKEN_ADDRESS, "L2BaseToken"),
+];
+
+/// Base gas cost of every precompile and system contract, see [`ZKsyncPrecompiles::cost_table`].
+const PRECOMPILE_BASE_COSTS: &[(Address, u64)] = &[
+    (address!("0000000000000000000000000000000000000001"), 3_000),
+    (address!("0000000000000000000000000000000000000002"), 60),
+    (address!("0000000000000000000000000000000000000003"), 600),
+    (address!("0000000000000000000000000000000000000004"), 15),
+    (address!("0000000000000000000000000000000000000005"), 200),
+    (address!("0000000000000000000000000000000000000006"), 150),
+    (address!("0000000000000000000000000000000000000007"), 6_000),
+    (address!("0000000000000000000000000000000000000008"), 45_000),
+    (CONTRACT_DEPLOYER_ADDRESS, deployer::BASE_COST),
+    (L1_MESSENGER_ADDRESS, l1_messenger::BASE_COST),
+    (L2_BASE_TOKEN_ADDRESS, l2_base_token::BASE_COST),
+];
+
+/// Function selectors explicitly handled by each natively implemented system contract.
+const SYSTEM_CONTRACT_SELECTORS: [(Address, &[(&[u8], &str)]); 3] = [
+    (
+        CONTRACT_DEPLOYER_ADDRESS,
+        &[(
+            deployer::SET_EVM_BYTECODE_DETAILS,
+            "setBytecodeDetailsEVM(address,bytes32,uint32,bytes32)",
+        )],
+    ),
+    (
+        L1_MESSENGER_ADDRESS,
+        &[(l1_messenger::SEND_TO_L1_SELECTOR, "sendToL1(bytes)")],
+    ),
+    (
+        L2_BASE_TOKEN_ADDRESS,
+        &[
+            (l2_base_token::WITHDRAW_SELECTOR, "withdraw(address)"),
+            (
+                l2_base_token::WITHDRAW_WITH_MESSAGE_SELECTOR,
+                "withdrawWithMessage(address,bytes)",
+            ),
+        ],
+    ),
+];
+
+/// Selectors handled by a system contract, see [`ZKsyncPrecompiles::coverage_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileCoverageReport {
+    /// Address of the system contract.
+    pub address: Address,
+    /// Explicitly handled selectors with their function signatures.
+    pub handled_selectors: Vec<([u8; 4], &'static str)>,
+    /// Whether any other selector is rejected with a revert.
+    pub unhandled_fallback: bool,
+}
+
+impl PrecompileCoverageReport {
+    /// Returns `true` if `selector` is explicitly handled.
+    pub fn handles(&self, selector: &[u8]) -> bool {
+        self.handled_selectors
+            .iter()
+            .any(|(handled, _)| handled[..] == *selector)
+    }
+}
+
+/// Kind of a precompile served by [`ZKsyncPrecompiles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrecompileKind {
+    /// Ethereum precompile that is part of the canonical set of the spec.
+    Ethereum,
+    /// Natively implemented ZKsync OS system contract.
+    ZkSystemContract,
+    /// Precompile added on top of the canonical set.
+    Custom,
+}
+
+/// Hook called with the address, the input and the result of every precompile call.
+pub type PrecompileCallLogger = Arc<dyn Fn(Address, &[u8], &InterpreterResult) + Send + Sync>;
+
 /// ZKsync OS precompile provider
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ZKsyncPrecompiles {
     /// Inner precompile provider is same as Ethereums.
     inner: EthPrecompiles,
     /// Spec id of the precompile provider.
     spec: ZkSpecId,
+    /// Optional hook logging every call, see [`ZKsyncPrecompiles::log_calls`].
+    call_logger: Option<PrecompileCallLogger>,
+    /// Precompiles added on top of the canonical set, reapplied on spec changes.
+    extra_precompiles: Vec<Precompile>,
+    /// Addresses removed from the canonical set, reapplied on spec changes.
+    excluded_precompiles: Vec<Address>,
+}
+
+impl core::fmt::Debug for ZKsyncPrecompiles {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ZKsyncPrecompiles")
+            .field("inner", &self.inner)
+            .field("spec", &self.spec)
+            .field("call_logger", &self.call_logger.is_some())
+            .field("extra_precompiles", &self.extra_precompiles)
+            .field("excluded_precompiles", &self.excluded_precompiles)
+            .finish()
+    }
 }
 
 impl ZKsyncPrecompiles {
     /// Create a new precompile provider with the given ZkSpec.
+    ///
+    /// The spec should match the one of the context's `CfgEnv`.
     #[inline]
+    #[must_use]
     pub fn new_with_spec(spec: ZkSpecId) -> Self {
         let precompiles = match spec {
-            ZkSpecId::Atlas => {
-                static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
-                INSTANCE.get_or_init(|| {
-                    let mut precompiles = Precompiles::default();
-                    // Generating the list instead of using default Cancun fork,
-                    // because we need to remove Blake2 and Point Evaluation
-                    precompiles.extend([
-                        secp256k1::ECRECOVER,
-                        hash::SHA256,
-                        hash::RIPEMD160,
-                        identity::FUN,
-                        modexp::BERLIN,
-                        bn254::add::ISTANBUL,
-                        bn254::mul::ISTANBUL,
-                        bn254::pair::ISTANBUL,
-                    ]);
-                    precompiles
-                })
-            }
+            ZkSpecId::Atlas => precompiles_for_atlas(),
         };
+        // SAFETY: `precompiles` is the canonical set of `spec`.
+        unsafe { Self::new_unchecked(spec, precompiles) }
+    }
+
+    /// Create a new precompile provider with the given ZkSpec and precompile set.
+    ///
+    /// # Safety
+    ///
+    /// `precompiles` must be the canonical set of `spec`, e.g. [`precompiles_for_atlas`] for
+    /// [`ZkSpecId::Atlas`]. Other sets break the assumptions of [`PrecompileKind`]
+    /// classification, and [`PrecompileProvider::set_spec`] silently replaces them.
+    #[inline]
+    pub const unsafe fn new_unchecked(spec: ZkSpecId, precompiles: &'static Precompiles) -> Self {
         Self {
             inner: EthPrecompiles {
                 precompiles,
                 spec: spec.into_eth_spec(),
             },
             spec,
+            call_logger: None,
+            extra_precompiles: Vec::new(),
+            excluded_precompiles: Vec::new(),
+        }
+    }
+
+    /// Create a precompile provider starting from the canonical set of the given ZkSpec,
+    /// removing the `excluded` addresses and adding the `extra` precompiles.
+    ///
+    /// The resulting [`Precompiles`] are leaked to obtain a `'static` reference. Identical sets
+    /// are leaked once and shared, so calling this per block or transaction does not grow
+    /// memory.
+    pub fn new_with_overrides(
+        spec: ZkSpecId,
+        extra: &[Precompile],
+        excluded: &[Address],
+    ) -> Result<Self, InvalidPrecompileOverride> {
+        Self::new_with_spec(spec).with_overrides(extra, excluded)
+    }
+
+    /// Applies the precompile changes of a custom chain, e.g. a ZK Stack chain settling on
+    /// ZKsync.
+    ///
+    /// With a [spec override](CustomChainConfig::spec_override) the changes apply to the
+    /// canonical set of that spec, otherwise to the current set. Shares the resulting
+    /// [`Precompiles`] like [`Self::new_with_overrides`].
+    pub fn extend_for_custom_chain(
+        self,
+        config: &CustomChainConfig,
+    ) -> Result<Self, InvalidPrecompileOverride> {
+        let base = match config.spec_override {
+            Some(spec) => Self {
+                call_logger: self.call_logger,
+                ..Self::new_with_spec(spec)
+            },
+            None => self,
+        };
+        base.with_overrides(&config.additional_precompiles, &config.disabled_precompiles)
+    }
+
+    /// Removes the `excluded` addresses from the current set and adds the `extra` precompiles.
+    fn with_overrides(
+        self,
+        extra: &[Precompile],
+        excluded: &[Address],
+    ) -> Result<Self, InvalidPrecompileOverride> {
+        if let Some(address) = excluded
+            .iter()
+            .find(|address| *address == secp256k1::ECRECOVER.address())
+        {
+            return Err(InvalidPrecompileOverride::RequiredExcluded(*address));
+        }
+        if let Some(precompile) = extra
+            .iter()
+            .find(|precompile| is_system_contract(precompile.address()))
+        {
+            return Err(InvalidPrecompileOverride::SystemContractAddress(
+                *precompile.address(),
+            ));
+        }
+
+        let mut precompiles = Precompiles::default();
+        precompiles.extend(
+            self.precompiles()
+                .inner()
+                .values()
+                .filter(|precompile| !excluded.contains(precompile.address()))
+                .chain(extra)
+                .cloned(),
+        );
+
+        // Recorded so that excluding then adding, or adding then excluding, an address gives
+        // the same set when reapplied to a canonical set.
+        let mut extra_precompiles = self.extra_precompiles;
+        extra_precompiles.retain(|precompile| !excluded.contains(precompile.address()));
+        extra_precompiles.extend_from_slice(extra);
+        let mut excluded_precompiles = self.excluded_precompiles;
+        excluded_precompiles.extend_from_slice(excluded);
+
+        Ok(Self {
+            inner: EthPrecompiles {
+                precompiles: intern_precompiles(precompiles),
+                spec: self.spec.into_eth_spec(),
+            },
+            spec: self.spec,
+            call_logger: self.call_logger,
+            extra_precompiles,
+            excluded_precompiles,
+        })
+    }
+
+    /// Switches to the canonical set of `spec`, keeping the call logger and reapplying the
+    /// overrides of [`Self::new_with_overrides`] and [`Self::extend_for_custom_chain`].
+    fn reset_to_spec(&mut self, spec: ZkSpecId) {
+        let current = core::mem::take(self);
+        let base = Self {
+            call_logger: current.call_logger,
+            ..Self::new_with_spec(spec)
+        };
+        *self = if current.extra_precompiles.is_empty() && current.excluded_precompiles.is_empty() {
+            base
+        } else {
+            base.with_overrides(&current.extra_precompiles, &current.excluded_precompiles)
+                .expect("overrides are validated independently of the spec")
+        };
+    }
+
+    /// Calls `log_fn` with the address, the input and the result of every precompile call,
+    /// e.g. for debugging.
+    pub fn log_calls(mut self, log_fn: PrecompileCallLogger) -> Self {
+        self.call_logger = Some(log_fn);
+        self
+    }
+
+    /// Removes the hook set with [`Self::log_calls`].
+    pub fn without_logging(mut self) -> Self {
+        self.call_logger = None;
+        self
+    }
+
+    /// Returns the name of the precompile or system contract at `address`, if known.
+    pub fn address_to_name(address: &Address) -> Option<&'static str> {
+        PRECOMPILE_NAMES
+            .iter()
+            .find(|(known, _)| known == address)
+            .map(|(_, name)| *name)
+    }
+
+    /// Formats `address` as its [name](Self::address_to_name), or as hex if unknown.
+    pub fn display_address(address: &Address) -> String {
+        match Self::address_to_name(address) {
+            Some(name) => name.to_string(),
+            None => format!("0x{address:x}"),
         }
     }
 
+    /// Reports the selectors handled by each natively implemented system contract, e.g. for
+    /// audit tooling.
+    ///
+    /// All system contracts revert on selectors they do not handle.
+    pub fn coverage_report(&self) -> Vec<PrecompileCoverageReport> {
+        SYSTEM_CONTRACT_SELECTORS
+            .iter()
+            .map(|(address, selectors)| PrecompileCoverageReport {
+                address: *address,
+                handled_selectors: selectors
+                    .iter()
+                    .map(|(selector, name)| {
+                        let selector = (*selector).try_into().expect("selectors are 4 bytes");
+                        (selector, *name)
+                    })
+                    .collect(),
+                unhandled_fallback: true,
+            })
+            .collect()
+    }
+
+    /// Lists the name and base gas cost of every served address, in ascending address order.
+    ///
+    /// The base cost is the cost of a call with empty input. Input dependent costs, e.g. per
+    /// word of hashed data, come on top.
+    pub fn cost_table(&self) -> Vec<(Address, &'static str, u64)> {
+        self.sorted_precompile_addresses()
+            .into_iter()
+            .filter_map(|address| {
+                let name = Self::address_to_name(&address)?;
+                let (_, cost) = PRECOMPILE_BASE_COSTS
+                    .iter()
+                    .find(|(known, _)| *known == address)?;
+                Some((address, name, *cost))
+            })
+            .collect()
+    }
+
     /// Precompiles getter.
     #[inline]
     pub fn precompiles(&self) -> &'static Precompiles {
         self.inner.precompiles
     }
-}
 
-impl<CTX> PrecompileProvider<CTX> for ZKsyncPrecompiles
-where
-    CTX: ContextTr<Cfg: Cfg<Spec = ZkSpecId>>,
-{
-    type Output = InterpreterResult;
+    /// Number of precompiles and system contracts served by the provider.
+    pub fn len(&self) -> usize {
+        self.precompiles().len() + ZKSYNC_SYSTEM_CONTRACTS.len()
+    }
 
-    #[inline]
-    fn set_spec(&mut self, spec: <CTX::Cfg as Cfg>::Spec) -> bool {
-        if spec == self.spec {
-            return false;
+    /// Addresses that are warm in every transaction regardless of the spec: the natively
+    /// implemented system contracts. Useful for pre-loading them from the database.
+    pub fn always_warm_addresses(&self) -> &'static [Address] {
+        &ZKSYNC_SYSTEM_CONTRACTS
+    }
+
+    /// All addresses served by the provider, Ethereum precompiles and system contracts, in
+    /// ascending order.
+    pub fn sorted_precompile_addresses(&self) -> Vec<Address> {
+        let mut addresses: Vec<Address> = self
+            .precompiles()
+            .addresses()
+            .chain(self.always_warm_addresses())
+            .copied()
+            .collect();
+        addresses.sort_unstable();
+        addresses
+    }
+
+    /// Stable identifier of the served precompile set: the keccak256 hash of the
+    /// [sorted](Self::sorted_precompile_addresses) addresses concatenated.
+    pub fn precompile_set_hash(&self) -> B256 {
+        let bytes: Vec<u8> = self
+            .sorted_precompile_addresses()
+            .iter()
+            .flat_map(|address| address.into_array())
+            .collect();
+        keccak256(bytes)
+    }
+
+    /// Returns `true` if the provider serves no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a human readable list of the served addresses and their kind.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for (address, kind) in self {
+            let _ = writeln!(summary, "{address}: {kind:?}");
         }
-        *self = Self::new_with_spec(spec);
-        true
+        summary
     }
 
-    #[inline]
-    fn run(
+    /// Runs a precompile without a context, returning its output and gas cost.
+    ///
+    /// Ethereum precompiles are pure functions of their input, so their results can be
+    /// precomputed and cached. Returns `None` for `ecrecover`, for system contracts, which
+    /// access the state, for unknown addresses and for failed or reverted calls.
+    #[cfg(feature = "static-precompile")]
+    pub fn try_run_static(&self, address: &Address, input: &[u8]) -> Option<(Bytes, u64)> {
+        if is_system_contract(address) || address == secp256k1::ECRECOVER.address() {
+            return None;
+        }
+        let output = self
+            .precompiles()
+            .get(address)?
+            .execute(input, u64::MAX)
+            .ok()?;
+        (!output.reverted).then_some((output.bytes, output.gas_used))
+    }
+
+    /// Runs the precompile or system contract at `address`.
+    fn run_inner<CTX: ZkContextTr>(
         &mut self,
         context: &mut CTX,
         address: &Address,
         inputs: &InputsImpl,
         is_static: bool,
         gas_limit: u64,
-    ) -> Result<Option<Self::Output>, String> {
-        // Closure to get vector calldata bytes
-        let get_input_bytes = || match &inputs.input {
-            revm::interpreter::CallInput::SharedBuffer(range) => {
-                if let Some(slice) = context.local().shared_memory_buffer_slice(range.clone()) {
-                    slice.to_vec()
-                } else {
-                    vec![]
-                }
-            }
-            revm::interpreter::CallInput::Bytes(bytes) => bytes.0.to_vec(),
-        };
+    ) -> Result<Option<InterpreterResult>, String> {
+        #[cfg(debug_assertions)]
+        self.assert_call_invariants(address, inputs, is_static);
+        let is_static = is_static || context.execution_mode() == ExecutionMode::StaticCall;
+
+        let get_input_bytes = || call_input_bytes(context, inputs);
         if *address == CONTRACT_DEPLOYER_ADDRESS {
             return Ok(Some(deployer_precompile_call(
                 context,
@@ -131,15 +494,373 @@ where
                 inputs.call_value,
                 &get_input_bytes(),
             )));
+        } else if (SYSTEM_CONTRACTS_START..=SYSTEM_CONTRACTS_END).contains(address)
+            && *address != BOOTLOADER_FORMAL_ADDRESS
+        {
+            // Reserved system contract address without a native implementation.
+            // Unless bytecode was deployed there, revert instead of executing empty code.
+            // The bootloader is not a contract, it can receive value like any account.
+            let is_empty_code = match context.journal_mut().load_account_code(*address) {
+                Ok(account) => account.info.is_empty_code_hash(),
+                Err(error) => {
+                    // Surfaced as a database error once the transaction halts.
+                    *context.error() = Err(ContextError::Db(error));
+                    return Ok(Some(InterpreterResult::new(
+                        InstructionResult::FatalExternalError,
+                        Bytes::new(),
+                        Gas::new(gas_limit),
+                    )));
+                }
+            };
+            if is_empty_code {
+                return Ok(Some(InterpreterResult::new(
+                    InstructionResult::Revert,
+                    Bytes::new(),
+                    Gas::new(gas_limit),
+                )));
+            }
         }
 
         self.inner
             .run(context, address, inputs, is_static, gas_limit)
     }
 
+    /// Checks the preconditions the EVM guarantees for a call to a ZKsync OS system contract.
+    ///
+    /// Returns a message describing the violated invariant, if any.
+    pub fn verify_call_invariants(
+        &self,
+        address: &Address,
+        inputs: &InputsImpl,
+        is_static: bool,
+    ) -> Option<String> {
+        if !is_system_contract(address) {
+            return None;
+        }
+        if is_static && !inputs.call_value.is_zero() {
+            return Some(format!(
+                "system contract {address} called with value {} in a static context",
+                inputs.call_value
+            ));
+        }
+        None
+    }
+
+    /// Panics if [`Self::verify_call_invariants`] reports a violation.
+    #[cfg(debug_assertions)]
+    fn assert_call_invariants(&self, address: &Address, inputs: &InputsImpl, is_static: bool) {
+        if let Some(message) = self.verify_call_invariants(address, inputs, is_static) {
+            panic!("{message}");
+        }
+    }
+
+    /// Checks the result of a call to the precompile or system contract at `address`.
+    ///
+    /// No call may hand back more gas than it was given. System contracts must also spend
+    /// all gas when running out of it and return ABI encoded outputs: a `bytes32` hash for
+    /// the L1 messenger and nothing for the others.
+    ///
+    /// Returns a message describing the violated invariant, if any.
+    pub fn verify_result_invariants(
+        &self,
+        address: &Address,
+        result: &InterpreterResult,
+        gas_limit: u64,
+    ) -> Option<String> {
+        let name = Self::display_address(address);
+        let remaining = result.gas.remaining();
+        if remaining > gas_limit {
+            return Some(format!(
+                "{name} returned {remaining} gas out of a {gas_limit} gas limit"
+            ));
+        }
+        if !is_system_contract(address) {
+            return None;
+        }
+        if result.result == InstructionResult::OutOfGas && remaining != 0 {
+            return Some(format!(
+                "{name} ran out of gas with {remaining} gas remaining"
+            ));
+        }
+        let expected_output_len = if *address == L1_MESSENGER_ADDRESS {
+            32
+        } else {
+            0
+        };
+        if result.result == InstructionResult::Return && result.output.len() != expected_output_len
+        {
+            return Some(format!(
+                "{name} returned {} bytes instead of {expected_output_len}",
+                result.output.len()
+            ));
+        }
+        None
+    }
+
+    /// Panics if [`Self::verify_result_invariants`] reports a violation.
+    #[cfg(debug_assertions)]
+    fn check_invariants_after_call(
+        &self,
+        address: &Address,
+        result: &InterpreterResult,
+        gas_limit: u64,
+    ) {
+        if let Some(message) = self.verify_result_invariants(address, result, gas_limit) {
+            panic!("{message}");
+        }
+    }
+}
+
+/// Returns the precompiles of [`ZkSpecId::Atlas`].
+pub fn precompiles_for_atlas() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = Precompiles::default();
+        // Generating the list instead of using default Cancun fork,
+        // because we need to remove Blake2 and Point Evaluation
+        precompiles.extend([
+            secp256k1::ECRECOVER,
+            hash::SHA256,
+            hash::RIPEMD160,
+            identity::FUN,
+            modexp::BERLIN,
+            bn254::add::ISTANBUL,
+            bn254::mul::ISTANBUL,
+            bn254::pair::ISTANBUL,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns a `'static` copy of `precompiles`, leaking it only the first time an identical set
+/// is seen.
+fn intern_precompiles(precompiles: Precompiles) -> &'static Precompiles {
+    type Key = Vec<(Address, PrecompileId, usize)>;
+    static INTERNED: OnceLock<Mutex<HashMap<Key, &'static Precompiles>>> = OnceLock::new();
+
+    // Precompiles are identified by their function too, custom ones may share an id.
+    let mut key: Key = precompiles
+        .inner()
+        .values()
+        .map(|precompile| {
+            (
+                *precompile.address(),
+                precompile.id().clone(),
+                *precompile.precompile() as usize,
+            )
+        })
+        .collect();
+    key.sort_unstable_by_key(|(address, ..)| *address);
+
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    *interned
+        .entry(key)
+        .or_insert_with(|| Box::leak(Box::new(precompiles)))
+}
+
+/// Returns `true` if the address belongs to a system contract handled by [`ZKsyncPrecompiles`].
+#[inline]
+fn is_system_contract(address: &Address) -> bool {
+    ZKSYNC_SYSTEM_CONTRACTS.contains(address)
+}
+
+/// Copies the calldata of a precompile call.
+fn call_input_bytes<CTX: ZkContextTr>(context: &CTX, inputs: &InputsImpl) -> Vec<u8> {
+    match &inputs.input {
+        CallInput::SharedBuffer(range) => context
+            .local()
+            .shared_memory_buffer_slice(range.clone())
+            .map(|slice| slice.to_vec())
+            .unwrap_or_default(),
+        CallInput::Bytes(bytes) => bytes.0.to_vec(),
+    }
+}
+
+impl IntoIterator for &ZKsyncPrecompiles {
+    type Item = (Address, PrecompileKind);
+    type IntoIter = vec::IntoIter<(Address, PrecompileKind)>;
+
+    /// Iterates over the served addresses in ascending order.
+    fn into_iter(self) -> Self::IntoIter {
+        let canonical = ZKsyncPrecompiles::new_with_spec(self.spec).precompiles();
+        let mut items: Vec<_> = self
+            .precompiles()
+            .addresses()
+            .map(|address| {
+                let kind = if canonical.contains(address) {
+                    PrecompileKind::Ethereum
+                } else {
+                    PrecompileKind::Custom
+                };
+                (*address, kind)
+            })
+            .chain(
+                ZKSYNC_SYSTEM_CONTRACTS
+                    .into_iter()
+                    .map(|address| (address, PrecompileKind::ZkSystemContract)),
+            )
+            .collect();
+        items.sort_unstable_by_key(|(address, _)| *address);
+        items.into_iter()
+    }
+}
+
+/// Precompile changes of a custom chain, see [`ZKsyncPrecompiles::extend_for_custom_chain`].
+#[derive(Clone, Debug, Default)]
+pub struct CustomChainConfig {
+    /// Chain id of the custom chain.
+    pub chain_id: u64,
+    /// Precompiles added on top of the Ethereum ones. System contract addresses are
+    /// reserved and rejected.
+    pub additional_precompiles: Vec<Precompile>,
+    /// Ethereum precompiles disabled on the chain.
+    pub disabled_precompiles: Vec<Address>,
+    /// Spec whose canonical precompile set the changes apply to, if not the current one.
+    pub spec_override: Option<ZkSpecId>,
+}
+
+/// Error returned by [`ZKsyncPrecompiles::new_with_overrides`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidPrecompileOverride {
+    /// A precompile required by the spec is excluded.
+    RequiredExcluded(Address),
+    /// An extra precompile is placed at a system contract address.
+    SystemContractAddress(Address),
+}
+
+impl core::fmt::Display for InvalidPrecompileOverride {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::RequiredExcluded(address) => {
+                write!(f, "precompile {address} is required and cannot be excluded")
+            }
+            Self::SystemContractAddress(address) => {
+                write!(f, "address {address} is reserved for a system contract")
+            }
+        }
+    }
+}
+
+impl core::error::Error for InvalidPrecompileOverride {}
+
+/// Error of a ZKsync OS system contract call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZkPrecompileError {
+    /// The call ran out of gas.
+    Oog,
+    /// The call reverted with the given output.
+    Revert(Bytes),
+    /// The calldata could not be decoded.
+    InvalidInput(String),
+    /// The selector is not supported by the system contract. Reverts with the selector as
+    /// output, so unhandled selectors can be told apart from other reverts.
+    UnsupportedSelector([u8; 4]),
+    /// The transaction already sent the maximum number of L2 -> L1 messages, see
+    /// [`ZkSpecId::max_l1_messages_per_tx`].
+    TooManyL1Messages {
+        /// Maximum number of messages per transaction.
+        limit: usize,
+    },
+}
+
+impl ZkPrecompileError {
+    /// Converts the error into the result returned to the caller, keeping `gas_limit` gas
+    /// unless the call ran out of gas.
+    pub fn into_interpreter_result(self, gas_limit: u64) -> InterpreterResult {
+        match self {
+            Self::Oog => {
+                InterpreterResult::new(InstructionResult::OutOfGas, [].into(), Gas::new(0))
+            }
+            Self::Revert(output) => {
+                InterpreterResult::new(InstructionResult::Revert, output, Gas::new(gas_limit))
+            }
+            Self::InvalidInput(_) | Self::TooManyL1Messages { .. } => {
+                InterpreterResult::new(InstructionResult::Revert, [].into(), Gas::new(gas_limit))
+            }
+            Self::UnsupportedSelector(selector) => InterpreterResult::new(
+                InstructionResult::Revert,
+                Bytes::copy_from_slice(&selector),
+                Gas::new(gas_limit),
+            ),
+        }
+    }
+}
+
+impl core::fmt::Display for ZkPrecompileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Oog => f.write_str("out of gas"),
+            Self::Revert(output) => write!(f, "reverted with {output}"),
+            Self::InvalidInput(reason) => write!(f, "invalid input: {reason}"),
+            Self::TooManyL1Messages { limit } => {
+                write!(f, "more than {limit} L2 -> L1 messages per transaction")
+            }
+            Self::UnsupportedSelector(selector) => {
+                write!(
+                    f,
+                    "unsupported selector {}",
+                    Bytes::copy_from_slice(selector)
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ZkPrecompileError {}
+
+impl From<ZkPrecompileError> for String {
+    fn from(error: ZkPrecompileError) -> Self {
+        error.to_string()
+    }
+}
+
+impl<CTX> PrecompileProvider<CTX> for ZKsyncPrecompiles
+where
+    CTX: ZkContextTr,
+{
+    type Output = InterpreterResult;
+
+    #[inline]
+    fn set_spec(&mut self, spec: <CTX::Cfg as Cfg>::Spec) -> bool {
+        if spec == self.spec {
+            return false;
+        }
+        self.reset_to_spec(spec);
+        true
+    }
+
+    #[inline]
+    fn run(
+        &mut self,
+        context: &mut CTX,
+        address: &Address,
+        inputs: &InputsImpl,
+        is_static: bool,
+        gas_limit: u64,
+    ) -> Result<Option<Self::Output>, String> {
+        let result = self.run_inner(context, address, inputs, is_static, gas_limit)?;
+        #[cfg(debug_assertions)]
+        if let Some(result) = &result {
+            self.check_invariants_after_call(address, result, gas_limit);
+        }
+        if let (Some(logger), Some(result)) = (&self.call_logger, &result) {
+            logger(*address, &call_input_bytes(context, inputs), result);
+        }
+        Ok(result)
+    }
+
     #[inline]
     fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
-        self.inner.warm_addresses()
+        // System contracts are warm like precompiles, so calling them is never charged
+        // the cold account access cost.
+        Box::new(
+            self.inner
+                .warm_addresses()
+                .chain(self.always_warm_addresses().iter().copied()),
+        )
     }
 
     #[inline]
@@ -148,8 +869,712 @@ where
     }
 }
 
+/// Uses the [latest](ZkSpecId::latest) spec. Use [`ZKsyncPrecompiles::new_with_spec`] to pin
+/// an older one.
 impl Default for ZKsyncPrecompiles {
     fn default() -> Self {
-        Self::new_with_spec(ZkSpecId::Atlas)
+        Self::new_with_spec(ZkSpecId::latest())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DefaultZk, ZKsyncTx,
+        api::{builder::ZkBuilder, default_ctx::ZkContext},
+    };
+    use revm::{
+        Context, ExecuteEvm,
+        context::TxEnv,
+        context_interface::Transaction,
+        database::{EmptyDB, InMemoryDB},
+        precompile::{PrecompileId, secp256r1},
+        primitives::{KECCAK_EMPTY, TxKind, U256},
+        state::AccountInfo,
+    };
+    use std::sync::Mutex;
+
+    fn inputs(address: Address, call_value: U256) -> InputsImpl {
+        InputsImpl {
+            target_address: address,
+            bytecode_address: Some(address),
+            caller_address: Address::ZERO,
+            input: CallInput::Bytes(Bytes::new()),
+            call_value,
+        }
+    }
+
+    /// Calls the precompile at `address` on a fresh in-memory context.
+    pub(super) fn call_with_mocked_context(
+        address: &Address,
+        calldata: &[u8],
+        gas_limit: u64,
+        caller: Address,
+        is_static: bool,
+        call_value: U256,
+    ) -> Option<InterpreterResult> {
+        let mut ctx = Context::default().with_db(InMemoryDB::default());
+        let input = InputsImpl {
+            target_address: *address,
+            bytecode_address: Some(*address),
+            caller_address: caller,
+            input: CallInput::Bytes(Bytes::copy_from_slice(calldata)),
+            call_value,
+        };
+        ZKsyncPrecompiles::default()
+            .run(&mut ctx, address, &input, is_static, gas_limit)
+            .unwrap()
+    }
+
+    fn call_deployer_mock(calldata: &[u8], caller: Address, is_static: bool) -> InterpreterResult {
+        call_with_mocked_context(
+            &CONTRACT_DEPLOYER_ADDRESS,
+            calldata,
+            100_000,
+            caller,
+            is_static,
+            U256::ZERO,
+        )
+        .unwrap()
+    }
+
+    fn call_l1_messenger_mock(calldata: &[u8], is_static: bool) -> InterpreterResult {
+        call_with_mocked_context(
+            &L1_MESSENGER_ADDRESS,
+            calldata,
+            100_000,
+            Address::ZERO,
+            is_static,
+            U256::ZERO,
+        )
+        .unwrap()
+    }
+
+    fn call_l2_base_token_mock(calldata: &[u8], is_static: bool) -> InterpreterResult {
+        call_with_mocked_context(
+            &L2_BASE_TOKEN_ADDRESS,
+            calldata,
+            100_000,
+            Address::ZERO,
+            is_static,
+            U256::ZERO,
+        )
+        .unwrap()
+    }
+
+    /// ABI encodes `words` after `selector`.
+    fn encode_call(selector: &[u8], words: &[U256]) -> Vec<u8> {
+        let mut calldata = selector.to_vec();
+        for word in words {
+            calldata.extend_from_slice(&word.to_be_bytes::<32>());
+        }
+        calldata
+    }
+
+    #[test]
+    fn deployer_selectors() {
+        let calldata = encode_call(
+            deployer::SET_EVM_BYTECODE_DETAILS,
+            &[
+                U256::from_be_bytes(Address::with_last_byte(0x42).into_word().0),
+                U256::from_be_bytes(KECCAK_EMPTY.0),
+                U256::ZERO,
+                U256::from_be_bytes(KECCAK_EMPTY.0),
+            ],
+        );
+        let genesis = deployer::L2_GENESIS_UPGRADE_ADDRESS;
+        assert!(call_deployer_mock(&calldata, genesis, false).is_ok());
+        assert!(call_deployer_mock(&calldata, genesis, true).is_revert());
+        assert!(call_deployer_mock(&calldata, Address::ZERO, false).is_revert());
+        assert!(call_deployer_mock(&calldata[..100], genesis, false).is_revert());
+        assert!(call_deployer_mock(&[0xde, 0xad, 0xbe, 0xef], genesis, false).is_revert());
+    }
+
+    #[test]
+    fn l1_messenger_selectors() {
+        let calldata = encode_call(
+            l1_messenger::SEND_TO_L1_SELECTOR,
+            &[U256::from(32), U256::ZERO],
+        );
+        assert!(call_l1_messenger_mock(&calldata, false).is_ok());
+        assert!(call_l1_messenger_mock(&calldata, true).is_revert());
+        assert!(call_l1_messenger_mock(&calldata[..36], false).is_revert());
+        assert!(call_l1_messenger_mock(&[0xde, 0xad, 0xbe, 0xef], false).is_revert());
+    }
+
+    #[test]
+    fn l2_base_token_selectors() {
+        let receiver = U256::from_be_bytes(Address::with_last_byte(0x42).into_word().0);
+        let withdraw = encode_call(l2_base_token::WITHDRAW_SELECTOR, &[receiver]);
+        assert!(call_l2_base_token_mock(&withdraw, false).is_ok());
+        assert!(call_l2_base_token_mock(&withdraw, true).is_revert());
+        assert!(call_l2_base_token_mock(&withdraw[..35], false).is_revert());
+
+        let withdraw_with_message = encode_call(
+            l2_base_token::WITHDRAW_WITH_MESSAGE_SELECTOR,
+            &[receiver, U256::from(64), U256::ZERO],
+        );
+        assert!(call_l2_base_token_mock(&withdraw_with_message, false).is_ok());
+        assert!(call_l2_base_token_mock(&withdraw_with_message, true).is_revert());
+        assert!(call_l2_base_token_mock(&withdraw_with_message[..67], false).is_revert());
+
+        assert!(call_l2_base_token_mock(&[0xde, 0xad, 0xbe, 0xef], false).is_revert());
+    }
+
+    #[test]
+    fn precompile_error_results() {
+        let oog = ZkPrecompileError::Oog.into_interpreter_result(100);
+        assert_eq!(oog.result, InstructionResult::OutOfGas);
+        assert_eq!(oog.gas.remaining(), 0);
+
+        let revert =
+            ZkPrecompileError::Revert(Bytes::from_static(&[1])).into_interpreter_result(100);
+        assert!(revert.is_revert());
+        assert_eq!(revert.output, Bytes::from_static(&[1]));
+        assert_eq!(revert.gas.remaining(), 100);
+
+        let invalid =
+            ZkPrecompileError::InvalidInput("short calldata".into()).into_interpreter_result(100);
+        assert!(invalid.is_revert());
+        assert!(invalid.output.is_empty());
+        assert_eq!(invalid.gas.remaining(), 100);
+
+        let unsupported = ZkPrecompileError::UnsupportedSelector([0xde, 0xad, 0xbe, 0xef])
+            .into_interpreter_result(100);
+        assert!(unsupported.is_revert());
+        assert_eq!(
+            unsupported.output,
+            Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(unsupported.gas.remaining(), 100);
+        assert_eq!(
+            String::from(ZkPrecompileError::UnsupportedSelector([
+                0xde, 0xad, 0xbe, 0xef
+            ])),
+            "unsupported selector 0xdeadbeef"
+        );
+    }
+
+    #[test]
+    fn l1_messenger_reverts_past_message_limit() {
+        let mut ctx = Context::default();
+        for _ in 0..ZkSpecId::Atlas.max_l1_messages_per_tx() {
+            ctx.log_l2_to_l1_message(Address::ZERO, &[]).unwrap();
+        }
+        let calldata = encode_call(
+            l1_messenger::SEND_TO_L1_SELECTOR,
+            &[U256::from(32), U256::ZERO],
+        );
+        let mut input = inputs(L1_MESSENGER_ADDRESS, U256::ZERO);
+        input.input = CallInput::Bytes(calldata.into());
+        let result = ZKsyncPrecompiles::default()
+            .run(&mut ctx, &L1_MESSENGER_ADDRESS, &input, false, 100_000)
+            .unwrap()
+            .unwrap();
+        assert!(result.is_revert());
+        assert_eq!(
+            ctx.l2_to_l1_messages().len(),
+            ZkSpecId::Atlas.max_l1_messages_per_tx()
+        );
+    }
+
+    #[test]
+    fn static_call_mode_reverts_state_changes() {
+        let mut calldata = l1_messenger::SEND_TO_L1_SELECTOR.to_vec();
+        calldata.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        calldata.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+        let mut input = inputs(L1_MESSENGER_ADDRESS, U256::ZERO);
+        input.input = CallInput::Bytes(calldata.into());
+
+        let mut ctx = Context::default();
+        let mut precompiles = ZKsyncPrecompiles::default();
+        let result = precompiles
+            .run(&mut ctx, &L1_MESSENGER_ADDRESS, &input, false, 100_000)
+            .unwrap()
+            .unwrap();
+        assert!(result.is_ok());
+
+        ctx.set_execution_mode(ExecutionMode::StaticCall);
+        let result = precompiles
+            .run(&mut ctx, &L1_MESSENGER_ADDRESS, &input, false, 100_000)
+            .unwrap()
+            .unwrap();
+        assert!(result.is_revert());
+    }
+
+    #[test]
+    fn precompile_set_hash_is_stable() {
+        let first = ZKsyncPrecompiles::new_with_spec(ZkSpecId::Atlas);
+        let second = ZKsyncPrecompiles::new_with_spec(ZkSpecId::Atlas);
+        assert_eq!(first.precompile_set_hash(), second.precompile_set_hash());
+
+        let addresses = first.sorted_precompile_addresses();
+        assert_eq!(addresses.len(), first.len());
+        assert!(addresses.is_sorted());
+        assert_eq!(addresses[0], *secp256k1::ECRECOVER.address());
+        assert_eq!(addresses.last(), Some(&L2_BASE_TOKEN_ADDRESS));
+    }
+
+    #[test]
+    fn cost_table_covers_warm_addresses() {
+        let precompiles = ZKsyncPrecompiles::default();
+        let table = precompiles.cost_table();
+        for address in PrecompileProvider::<ZkContext<EmptyDB>>::warm_addresses(&precompiles) {
+            assert!(
+                table.iter().any(|(known, _, _)| *known == address),
+                "{address} has no cost"
+            );
+        }
+        assert!(table.contains(&(L2_BASE_TOKEN_ADDRESS, "L2BaseToken", 10)));
+    }
+
+    #[test]
+    fn coverage_report() {
+        let report = ZKsyncPrecompiles::default().coverage_report();
+        let handled = |address: Address| -> Vec<&str> {
+            let entry = report
+                .iter()
+                .find(|entry| entry.address == address)
+                .unwrap();
+            assert!(entry.unhandled_fallback);
+            entry
+                .handled_selectors
+                .iter()
+                .map(|(_, name)| *name)
+                .collect()
+        };
+        assert_eq!(
+            handled(L2_BASE_TOKEN_ADDRESS),
+            ["withdraw(address)", "withdrawWithMessage(address,bytes)"]
+        );
+        assert_eq!(
+            handled(CONTRACT_DEPLOYER_ADDRESS),
+            ["setBytecodeDetailsEVM(address,bytes32,uint32,bytes32)"]
+        );
+        assert_eq!(report.len(), ZKSYNC_SYSTEM_CONTRACTS.len());
+    }
+
+    #[test]
+    fn pubdata_estimate_is_upper_bound() {
+        let mut calldata = l1_messenger::SEND_TO_L1_SELECTOR.to_vec();
+        calldata.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        calldata.extend_from_slice(&U256::from(64).to_be_bytes::<32>());
+        calldata.extend_from_slice(&[0xab; 64]);
+        let tx = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .kind(TxKind::Call(L1_MESSENGER_ADDRESS))
+                    .data(calldata.clone().into()),
+            )
+            .build_fill();
+        let mut input = inputs(L1_MESSENGER_ADDRESS, U256::ZERO);
+        input.input = CallInput::Bytes(calldata.into());
+
+        let mut ctx = Context::default();
+        let result = ZKsyncPrecompiles::default()
+            .run(&mut ctx, &L1_MESSENGER_ADDRESS, &input, false, 100_000)
+            .unwrap()
+            .unwrap();
+        assert!(result.is_ok());
+        assert_eq!(ctx.pubdata_written(), 64);
+        assert!(tx.estimate_pubdata_bytes(ZkSpecId::Atlas) >= ctx.pubdata_written());
+        assert_eq!(
+            tx.estimate_pubdata_bytes_with_storage_writes(ZkSpecId::Atlas, 0),
+            tx.input().len() as u64
+        );
+    }
+
+    #[test]
+    fn system_contracts_are_warm() {
+        let precompiles = ZKsyncPrecompiles::default();
+        let warm: Vec<Address> =
+            PrecompileProvider::<ZkContext<EmptyDB>>::warm_addresses(&precompiles).collect();
+        assert_eq!(warm.len(), precompiles.len());
+        for address in precompiles.always_warm_addresses() {
+            assert!(warm.contains(address));
+        }
+    }
+
+    #[test]
+    fn default_uses_latest_spec() {
+        assert_eq!(ZKsyncPrecompiles::default().spec, ZkSpecId::latest());
+    }
+
+    #[test]
+    fn call_invariants() {
+        let precompiles = ZKsyncPrecompiles::default();
+        let valid = inputs(L2_BASE_TOKEN_ADDRESS, U256::ZERO);
+        assert!(
+            precompiles
+                .verify_call_invariants(&L2_BASE_TOKEN_ADDRESS, &valid, true)
+                .is_none()
+        );
+
+        let with_value = inputs(L2_BASE_TOKEN_ADDRESS, U256::from(1));
+        assert!(
+            precompiles
+                .verify_call_invariants(&L2_BASE_TOKEN_ADDRESS, &with_value, false)
+                .is_none()
+        );
+        assert!(
+            precompiles
+                .verify_call_invariants(&L2_BASE_TOKEN_ADDRESS, &with_value, true)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn result_invariants() {
+        // Every call goes through `check_invariants_after_call` in debug builds.
+        for address in ZKsyncPrecompiles::default().sorted_precompile_addresses() {
+            for calldata in [&[][..], &[0xde, 0xad, 0xbe, 0xef]] {
+                call_with_mocked_context(
+                    &address,
+                    calldata,
+                    100_000,
+                    Address::ZERO,
+                    false,
+                    U256::ZERO,
+                );
+            }
+        }
+        let calldata = encode_call(
+            l1_messenger::SEND_TO_L1_SELECTOR,
+            &[U256::from(32), U256::ZERO],
+        );
+        assert!(call_l1_messenger_mock(&calldata, false).is_ok());
+
+        let precompiles = ZKsyncPrecompiles::default();
+        let result = |result, output: &'static [u8], remaining| {
+            InterpreterResult::new(result, Bytes::from_static(output), Gas::new(remaining))
+        };
+        let ecrecover = address!("0000000000000000000000000000000000000001");
+        for (address, result, valid) in [
+            (
+                ecrecover,
+                result(InstructionResult::PrecompileOOG, &[], 100),
+                true,
+            ),
+            (
+                ecrecover,
+                result(InstructionResult::Return, &[], 101),
+                false,
+            ),
+            (
+                L2_BASE_TOKEN_ADDRESS,
+                result(InstructionResult::Return, &[], 100),
+                true,
+            ),
+            (
+                L2_BASE_TOKEN_ADDRESS,
+                result(InstructionResult::Return, &[0; 32], 100),
+                false,
+            ),
+            (
+                L2_BASE_TOKEN_ADDRESS,
+                result(InstructionResult::OutOfGas, &[], 0),
+                true,
+            ),
+            (
+                L2_BASE_TOKEN_ADDRESS,
+                result(InstructionResult::OutOfGas, &[], 1),
+                false,
+            ),
+            (
+                L1_MESSENGER_ADDRESS,
+                result(InstructionResult::Return, &[0; 32], 100),
+                true,
+            ),
+            (
+                L1_MESSENGER_ADDRESS,
+                result(InstructionResult::Return, &[0; 31], 100),
+                false,
+            ),
+            (
+                L1_MESSENGER_ADDRESS,
+                result(InstructionResult::Revert, &[], 100),
+                true,
+            ),
+        ] {
+            assert_eq!(
+                precompiles
+                    .verify_result_invariants(&address, &result, 100)
+                    .is_none(),
+                valid,
+                "{address}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn unimplemented_system_contract_reverts() {
+        let mut ctx = Context::default();
+        let mut precompiles = ZKsyncPrecompiles::default();
+        let address = address!("0000000000000000000000000000000000008002");
+        let result = precompiles
+            .run(
+                &mut ctx,
+                &address,
+                &inputs(address, U256::ZERO),
+                false,
+                1000,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::Revert);
+        assert_eq!(result.gas.remaining(), 1000);
+    }
+
+    #[test]
+    fn overridden_sets_are_shared() {
+        let build = || {
+            ZKsyncPrecompiles::new_with_overrides(
+                ZkSpecId::Atlas,
+                &[secp256r1::P256VERIFY],
+                &[*hash::RIPEMD160.address()],
+            )
+            .unwrap()
+        };
+        assert!(core::ptr::eq(build().precompiles(), build().precompiles()));
+
+        let other = ZKsyncPrecompiles::new_with_overrides(ZkSpecId::Atlas, &[], &[]).unwrap();
+        assert!(!core::ptr::eq(build().precompiles(), other.precompiles()));
+    }
+
+    #[test]
+    fn spec_change_keeps_overrides() {
+        let config = CustomChainConfig {
+            additional_precompiles: vec![secp256r1::P256VERIFY],
+            disabled_precompiles: vec![*hash::RIPEMD160.address()],
+            ..Default::default()
+        };
+        let mut precompiles = ZKsyncPrecompiles::default()
+            .log_calls(Arc::new(|_, _, _| {}))
+            .extend_for_custom_chain(&config)
+            .unwrap();
+        let expected = precompiles.sorted_precompile_addresses();
+
+        precompiles.reset_to_spec(ZkSpecId::Atlas);
+        assert_eq!(precompiles.sorted_precompile_addresses(), expected);
+        assert!(precompiles.call_logger.is_some());
+        assert!(
+            precompiles
+                .precompiles()
+                .contains(secp256r1::P256VERIFY.address())
+        );
+        assert!(
+            !precompiles
+                .precompiles()
+                .contains(hash::RIPEMD160.address())
+        );
+    }
+
+    #[test]
+    fn value_transfer_to_bootloader() {
+        let caller = Address::with_last_byte(0x42);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(100)));
+        let mut evm = Context::default().with_db(db).build_zk();
+        let tx = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .caller(caller)
+                    .kind(TxKind::Call(BOOTLOADER_FORMAL_ADDRESS))
+                    .value(U256::from(10)),
+            )
+            .build_fill();
+        let output = evm.transact(tx).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(
+            output.state[&BOOTLOADER_FORMAL_ADDRESS].info.balance,
+            U256::from(10)
+        );
+    }
+
+    #[test]
+    fn precompile_kinds() {
+        let precompiles =
+            ZKsyncPrecompiles::new_with_overrides(ZkSpecId::Atlas, &[secp256r1::P256VERIFY], &[])
+                .unwrap();
+        let items: Vec<_> = (&precompiles).into_iter().collect();
+        assert_eq!(items.len(), precompiles.len());
+
+        let system_contracts: Vec<_> = items
+            .iter()
+            .filter(|(_, kind)| *kind == PrecompileKind::ZkSystemContract)
+            .map(|(address, _)| *address)
+            .collect();
+        assert_eq!(system_contracts, ZKSYNC_SYSTEM_CONTRACTS);
+        assert!(items.contains(&(*secp256r1::P256VERIFY.address(), PrecompileKind::Custom)));
+        assert!(items.contains(&(*secp256k1::ECRECOVER.address(), PrecompileKind::Ethereum)));
+    }
+
+    #[test]
+    fn new_unchecked_runs_precompiles() {
+        let mut ctx = Context::default();
+        // SAFETY: canonical Atlas precompiles.
+        let mut precompiles =
+            unsafe { ZKsyncPrecompiles::new_unchecked(ZkSpecId::Atlas, precompiles_for_atlas()) };
+        let address = *identity::FUN.address();
+        let mut input = inputs(address, U256::ZERO);
+        input.input = CallInput::Bytes(Bytes::from_static(&[1, 2, 3]));
+        let result = precompiles
+            .run(&mut ctx, &address, &input, false, 1000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(result.output, Bytes::from_static(&[1, 2, 3]));
+    }
+
+    #[test]
+    #[cfg(feature = "static-precompile")]
+    fn try_run_static_matches_run() {
+        let mut ctx = Context::default();
+        let mut precompiles = ZKsyncPrecompiles::default();
+        for address in [
+            *hash::SHA256.address(),
+            *hash::RIPEMD160.address(),
+            *identity::FUN.address(),
+        ] {
+            let data = Bytes::from_static(b"zksync");
+            let (output, gas_used) = precompiles.try_run_static(&address, &data).unwrap();
+
+            let mut input = inputs(address, U256::ZERO);
+            input.input = CallInput::Bytes(data);
+            let result = precompiles
+                .run(&mut ctx, &address, &input, false, 10_000)
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.output, output);
+            assert_eq!(result.gas.spent(), gas_used);
+        }
+        assert_eq!(precompiles.try_run_static(&L1_MESSENGER_ADDRESS, &[]), None);
+        assert_eq!(
+            precompiles.try_run_static(secp256k1::ECRECOVER.address(), &[0; 128]),
+            None
+        );
+    }
+
+    #[test]
+    fn log_calls() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let logged = calls.clone();
+        let precompiles =
+            ZKsyncPrecompiles::default().log_calls(Arc::new(move |address, input, result| {
+                logged
+                    .lock()
+                    .unwrap()
+                    .push((address, input.to_vec(), result.result));
+            }));
+
+        let mut data = l2_base_token::WITHDRAW_SELECTOR.to_vec();
+        data.extend_from_slice(Address::with_last_byte(0x42).into_word().as_slice());
+        let tx = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .kind(TxKind::Call(L2_BASE_TOKEN_ADDRESS))
+                    .data(data.clone().into()),
+            )
+            .build_fill();
+        let mut evm = Context::default().build_zk().with_precompiles(precompiles);
+        assert!(evm.transact(tx).unwrap().result.is_success());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            *calls,
+            [(L2_BASE_TOKEN_ADDRESS, data, InstructionResult::Return)]
+        );
+    }
+
+    #[test]
+    fn every_warm_address_has_a_name() {
+        let precompiles = ZKsyncPrecompiles::default();
+        for address in PrecompileProvider::<ZkContext<EmptyDB>>::warm_addresses(&precompiles) {
+            assert!(
+                ZKsyncPrecompiles::address_to_name(&address).is_some(),
+                "{address} has no name"
+            );
+        }
+        assert_eq!(
+            ZKsyncPrecompiles::display_address(&L2_BASE_TOKEN_ADDRESS),
+            "L2BaseToken"
+        );
+        assert_eq!(
+            ZKsyncPrecompiles::display_address(&Address::with_last_byte(0x42)),
+            "0x0000000000000000000000000000000000000042"
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        let precompiles =
+            ZKsyncPrecompiles::new_with_overrides(ZkSpecId::Atlas, &[secp256r1::P256VERIFY], &[])
+                .unwrap();
+        assert!(
+            precompiles
+                .precompiles()
+                .contains(secp256r1::P256VERIFY.address())
+        );
+        assert_eq!(
+            precompiles.precompiles().len(),
+            ZKsyncPrecompiles::default().precompiles().len() + 1
+        );
+
+        let identity = *identity::FUN.address();
+        let precompiles =
+            ZKsyncPrecompiles::new_with_overrides(ZkSpecId::Atlas, &[], &[identity]).unwrap();
+        assert!(!precompiles.precompiles().contains(&identity));
+
+        let ecrecover = *secp256k1::ECRECOVER.address();
+        assert_eq!(
+            ZKsyncPrecompiles::new_with_overrides(ZkSpecId::Atlas, &[], &[ecrecover]).unwrap_err(),
+            InvalidPrecompileOverride::RequiredExcluded(ecrecover)
+        );
+    }
+
+    #[test]
+    fn custom_chain() {
+        let identity = *identity::FUN.address();
+        let config = CustomChainConfig {
+            chain_id: 0x1234,
+            additional_precompiles: vec![secp256r1::P256VERIFY],
+            disabled_precompiles: vec![identity],
+            spec_override: Some(ZkSpecId::Atlas),
+        };
+        let precompiles = ZKsyncPrecompiles::default()
+            .extend_for_custom_chain(&config)
+            .unwrap();
+        assert!(
+            precompiles
+                .precompiles()
+                .contains(secp256r1::P256VERIFY.address())
+        );
+        assert!(!precompiles.precompiles().contains(&identity));
+        assert_eq!(precompiles.len(), ZKsyncPrecompiles::default().len());
+
+        let config = CustomChainConfig {
+            additional_precompiles: vec![Precompile::new(
+                PrecompileId::Custom("system".into()),
+                L1_MESSENGER_ADDRESS,
+                identity::identity_run,
+            )],
+            ..Default::default()
+        };
+        assert_eq!(
+            ZKsyncPrecompiles::default()
+                .extend_for_custom_chain(&config)
+                .unwrap_err(),
+            InvalidPrecompileOverride::SystemContractAddress(L1_MESSENGER_ADDRESS)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "in a static context")]
+    fn call_invariants_panic_in_debug() {
+        let precompiles = ZKsyncPrecompiles::default();
+        let with_value = inputs(L2_BASE_TOKEN_ADDRESS, U256::from(1));
+        precompiles.assert_call_invariants(&L2_BASE_TOKEN_ADDRESS, &with_value, true);
     }
 }
@@ -1,13 +1,12 @@
 use revm::{
     Database,
-    context::{Cfg, JournalTr},
-    context_interface::ContextTr,
+    context::JournalTr,
     interpreter::{Gas, InstructionResult, InterpreterResult},
     primitives::{Address, B256, Bytes, U256, address},
     state::Bytecode,
 };
 
-use crate::ZkSpecId;
+use crate::{api::exec::ZkContextTr, precompiles::ZkPrecompileError};
 
 // setBytecodeDetailsEVM(address,bytes32,uint32,bytes32) - f6eca0b0
 pub const SET_EVM_BYTECODE_DETAILS: &[u8] = &[0xf6, 0xec, 0xa0, 0xb0];
@@ -19,6 +18,9 @@ pub const L2_GENESIS_UPGRADE_ADDRESS: Address =
 
 pub const MAX_CODE_SIZE: usize = 0x6000;
 
+/// Gas charged for every call, on top of the cost of the called function.
+pub const BASE_COST: u64 = 10;
+
 /// Run the deployer precompile.
 pub fn deployer_precompile_call<CTX>(
     ctx: &mut CTX,
@@ -29,13 +31,13 @@ pub fn deployer_precompile_call<CTX>(
     mut calldata: &[u8],
 ) -> InterpreterResult
 where
-    CTX: ContextTr<Cfg: Cfg<Spec = ZkSpecId>>,
+    CTX: ZkContextTr,
 {
     let error = || {
         InterpreterResult::new(
             InstructionResult::Revert,
             [].into(),
-            Gas::new(gas_limit - 10),
+            Gas::new(gas_limit - BASE_COST),
         )
     };
     if call_value != U256::ZERO {
@@ -89,9 +91,17 @@ where
                 return error();
             }
 
-            let bytecode = ctx.db_mut().code_by_hash(bytecode_hash).expect(
-                "The bytecode is expected to be pre-loaded for any deployer precompile call",
-            );
+            // Bytecodes from the transaction factory deps take precedence over the database.
+            let bytecode = match ctx.get_bytecode_by_hash(bytecode_hash) {
+                Some(bytecode) => bytecode.clone(),
+                None => match ctx.db_mut().code_by_hash(bytecode_hash) {
+                    Ok(bytecode) => bytecode,
+                    Err(_) => return error(),
+                },
+            };
+            if bytecode.original_bytes().len() < bytecode_length as usize {
+                return error();
+            }
 
             let bytecode_padded = Bytecode::new_legacy(Bytes::copy_from_slice(
                 &bytecode.original_bytes()[0..bytecode_length as usize],
@@ -101,12 +111,89 @@ where
                 .warm_account(address)
                 .expect("warm account");
             ctx.journal_mut().set_code(address, bytecode_padded);
+            ctx.add_pubdata_written(bytecode_length as u64);
             InterpreterResult::new(
                 InstructionResult::Return,
                 [].into(),
-                Gas::new(gas_limit - 10),
+                Gas::new(gas_limit - BASE_COST),
+            )
+        }
+        _ => ZkPrecompileError::UnsupportedSelector(selector)
+            .into_interpreter_result(gas_limit - BASE_COST),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultZk, precompiles::tests::call_with_mocked_context};
+    use revm::{Context, context::ContextTr};
+    use std::vec::Vec;
+
+    #[test]
+    fn selectors_are_covered() {
+        // Every selector declared in this file must be handled by a match arm.
+        for selector in [SET_EVM_BYTECODE_DETAILS] {
+            let result = call_with_mocked_context(
+                &CONTRACT_DEPLOYER_ADDRESS,
+                selector,
+                100_000,
+                Address::ZERO,
+                false,
+                U256::ZERO,
             )
+            .unwrap();
+            assert_ne!(result.output.as_ref(), selector, "unhandled selector");
         }
-        _ => error(),
+        // The fallback reverts with the selector.
+        let unknown = [0xde, 0xad, 0xbe, 0xef];
+        let result = call_with_mocked_context(
+            &CONTRACT_DEPLOYER_ADDRESS,
+            &unknown,
+            100_000,
+            Address::ZERO,
+            false,
+            U256::ZERO,
+        )
+        .unwrap();
+        assert_eq!(result.output.as_ref(), unknown);
+    }
+
+    #[test]
+    fn set_bytecode_from_registry() {
+        let mut ctx = Context::default();
+        let code = Bytecode::new_legacy(Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]));
+        let hash = code.hash_slow();
+        ctx.save_bytecode(code);
+
+        let address = Address::with_last_byte(0x42);
+        let mut calldata = Vec::from(SET_EVM_BYTECODE_DETAILS);
+        calldata.extend_from_slice(address.into_word().as_slice());
+        calldata.extend_from_slice(hash.as_slice());
+        calldata.extend_from_slice(&U256::from(5).to_be_bytes::<32>());
+        calldata.extend_from_slice(hash.as_slice());
+
+        let result = deployer_precompile_call(
+            &mut ctx,
+            L2_GENESIS_UPGRADE_ADDRESS,
+            false,
+            100,
+            U256::ZERO,
+            &calldata,
+        );
+        assert!(result.is_ok());
+        assert_eq!(ctx.journal_mut().code(address).unwrap().data.len(), 5);
+
+        // Unknown hashes revert instead of panicking.
+        calldata[36..68].copy_from_slice(B256::with_last_byte(1).as_slice());
+        let result = deployer_precompile_call(
+            &mut ctx,
+            L2_GENESIS_UPGRADE_ADDRESS,
+            false,
+            100,
+            U256::ZERO,
+            &calldata,
+        );
+        assert!(result.is_revert());
     }
 }
@@ -1,16 +1,14 @@
 use revm::{
-    context::{Cfg, JournalTr},
-    context_interface::ContextTr,
     interpreter::{
         Gas, InstructionResult, InterpreterResult,
         gas::{KECCAK256, KECCAK256WORD, LOG, LOGDATA, LOGTOPIC},
     },
-    primitives::{Address, B256, Bytes, Log, LogData, U256, address, keccak256},
+    primitives::{Address, B256, Bytes, Log, LogData, U256, address},
 };
 use std::vec;
 use std::vec::Vec;
 
-use crate::ZkSpecId;
+use crate::{api::exec::ZkContextTr, precompiles::ZkPrecompileError};
 
 // sendToL1(bytes) - 62f84b24
 pub const SEND_TO_L1_SELECTOR: &[u8] = &[0x62, 0xf8, 0x4b, 0x24];
@@ -22,6 +20,9 @@ const L1_MESSAGE_SENT_TOPIC: [u8; 32] = [
 
 pub const L1_MESSENGER_ADDRESS: Address = address!("0000000000000000000000000000000000008008");
 
+/// Gas charged for every call, on top of the cost of the called function.
+pub const BASE_COST: u64 = 10;
+
 #[inline(always)]
 fn b160_to_b256(addr: Address) -> B256 {
     let mut out = [0u8; 32];
@@ -29,6 +30,25 @@ fn b160_to_b256(addr: Address) -> B256 {
     B256::from(out)
 }
 
+/// Builds the `L1MessageSent(address,bytes32,bytes)` event of a message sent by `sender`.
+pub fn l1_message_sent_log(sender: Address, message_hash: B256, message: &[u8]) -> Log {
+    let topics = vec![
+        B256::from_slice(&L1_MESSAGE_SENT_TOPIC),
+        b160_to_b256(sender),
+        message_hash,
+    ];
+    // ABI encoded `bytes`: offset, length and the message padded to a multiple of 32 bytes.
+    let mut data = Vec::with_capacity(64 + message.len().next_multiple_of(32));
+    data.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+    data.extend_from_slice(&U256::from(message.len()).to_be_bytes::<32>());
+    data.extend_from_slice(message);
+    data.resize(64 + message.len().next_multiple_of(32), 0);
+    Log {
+        address: L1_MESSENGER_ADDRESS,
+        data: LogData::new_unchecked(topics, Bytes::from(data)),
+    }
+}
+
 /// Run the L1 messenger precompile.
 pub fn l1_messenger_precompile_call<CTX>(
     ctx: &mut CTX,
@@ -39,13 +59,13 @@ pub fn l1_messenger_precompile_call<CTX>(
     mut calldata: &[u8],
 ) -> InterpreterResult
 where
-    CTX: ContextTr<Cfg: Cfg<Spec = ZkSpecId>>,
+    CTX: ZkContextTr,
 {
     let mut gas = Gas::new(gas_limit);
-    let oog_error = || InterpreterResult::new(InstructionResult::OutOfGas, [].into(), Gas::new(0));
+    let oog_error = || ZkPrecompileError::Oog.into_interpreter_result(gas_limit);
     let error = move || InterpreterResult::new(InstructionResult::Revert, [].into(), gas.clone());
 
-    if !gas.record_cost(10) {
+    if !gas.record_cost(BASE_COST) {
         return oog_error();
     }
 
@@ -140,19 +160,12 @@ where
             if !gas.record_cost(needed_gas) {
                 return oog_error();
             }
-            let message_hash = keccak256(message);
-            let topics = vec![
-                B256::from_slice(&L1_MESSAGE_SENT_TOPIC),
-                b160_to_b256(caller),
-                message_hash,
-            ];
-            let log = Log {
-                address: L1_MESSENGER_ADDRESS,
-                data: LogData::new_unchecked(topics, Bytes::from(Vec::from(calldata))),
+            let message_hash = match ctx.log_l2_to_l1_message(caller, message) {
+                Ok(message_hash) => message_hash,
+                Err(error) => return error.into_interpreter_result(gas.remaining()),
             };
-            ctx.journal_mut().log(log);
             InterpreterResult::new(InstructionResult::Return, message_hash.into(), gas)
         }
-        _ => error(),
+        _ => ZkPrecompileError::UnsupportedSelector(selector).into_interpreter_result(gas_limit),
     }
 }
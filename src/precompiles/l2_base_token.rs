@@ -1,15 +1,18 @@
 use std::vec::Vec;
 
 use revm::{
-    context::{Cfg, ContextTr, JournalTr},
+    context::JournalTr,
     interpreter::{Gas, InstructionResult, InterpreterResult},
     primitives::{Address, U256, address},
 };
 
-use crate::ZkSpecId;
+use crate::{api::exec::ZkContextTr, precompiles::ZkPrecompileError};
 
 pub const L2_BASE_TOKEN_ADDRESS: Address = address!("000000000000000000000000000000000000800a");
 
+/// Gas charged for every call, on top of the cost of the called function.
+pub const BASE_COST: u64 = 10;
+
 // withdraw(address) - 51cff8d9
 pub const WITHDRAW_SELECTOR: &[u8] = &[0x51, 0xcf, 0xf8, 0xd9];
 
@@ -29,13 +32,13 @@ pub fn l2_base_token_precompile_call<CTX>(
     calldata: &[u8],
 ) -> InterpreterResult
 where
-    CTX: ContextTr<Cfg: Cfg<Spec = ZkSpecId>>,
+    CTX: ZkContextTr,
 {
     let error = || {
         InterpreterResult::new(
             InstructionResult::Revert,
             [].into(),
-            Gas::new(gas_limit - 10),
+            Gas::new(gas_limit - BASE_COST),
         )
     };
     if calldata.len() < 4 {
@@ -230,6 +233,40 @@ where
 
             InterpreterResult::new(InstructionResult::Return, [].into(), Gas::new(gas_limit))
         }
-        _ => error(),
+        _ => ZkPrecompileError::UnsupportedSelector(selector)
+            .into_interpreter_result(gas_limit - BASE_COST),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precompiles::tests::call_with_mocked_context;
+
+    fn call_l2_base_token(calldata: &[u8]) -> InterpreterResult {
+        call_with_mocked_context(
+            &L2_BASE_TOKEN_ADDRESS,
+            calldata,
+            100_000,
+            Address::ZERO,
+            false,
+            U256::ZERO,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn selectors_are_covered() {
+        // Every selector declared in this file must be handled by a match arm, except
+        // `FINALIZE_ETH_WITHDRAWAL_SELECTOR`: it is encoded into the withdrawal message for L1,
+        // never called on L2.
+        for selector in [WITHDRAW_SELECTOR, WITHDRAW_WITH_MESSAGE_SELECTOR] {
+            let result = call_l2_base_token(selector);
+            assert_ne!(result.output.as_ref(), selector, "unhandled selector");
+        }
+        // The fallback reverts with the selector.
+        let result = call_l2_base_token(FINALIZE_ETH_WITHDRAWAL_SELECTOR);
+        assert!(result.is_revert());
+        assert_eq!(result.output.as_ref(), FINALIZE_ETH_WITHDRAWAL_SELECTOR);
     }
 }
@@ -10,6 +10,21 @@ pub enum ZkHaltReason {
     FailedDeposit,
 }
 
+/// Exit code of a transaction halted by the EVM.
+pub const EXIT_CODE_HALT: u8 = 1;
+/// Exit code of a failed deposit.
+pub const EXIT_CODE_FAILED_DEPOSIT: u8 = 2;
+
+impl ZkHaltReason {
+    /// Returns the stable numeric exit code of the halt reason.
+    pub const fn exit_code(&self) -> u8 {
+        match self {
+            Self::Base(_) => EXIT_CODE_HALT,
+            Self::FailedDeposit => EXIT_CODE_FAILED_DEPOSIT,
+        }
+    }
+}
+
 impl From<HaltReason> for ZkHaltReason {
     fn from(value: HaltReason) -> Self {
         Self::Base(value)
@@ -26,3 +41,21 @@ impl TryFrom<ZkHaltReason> for HaltReason {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::context_interface::result::OutOfGasError;
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(
+            ZkHaltReason::Base(HaltReason::OutOfGas(OutOfGasError::Basic)).exit_code(),
+            EXIT_CODE_HALT
+        );
+        assert_eq!(
+            ZkHaltReason::FailedDeposit.exit_code(),
+            EXIT_CODE_FAILED_DEPOSIT
+        );
+    }
+}
@@ -1,6 +1,13 @@
 //! Contains the `[ZkSpecId]` type and its implementation.
-use core::str::FromStr;
-use revm::primitives::hardfork::{SpecId, UnknownHardfork};
+use crate::{
+    handler::MAX_PUBDATA_PER_BATCH, transaction::abstraction::BOOTLOADER_TX_ENCODING_OVERHEAD,
+};
+use core::{ops::RangeInclusive, str::FromStr};
+use revm::{
+    interpreter::gas::{STANDARD_TOKEN_COST, get_tokens_in_calldata},
+    primitives::hardfork::{SpecId, UnknownHardfork},
+};
+use std::vec::Vec;
 
 /// ZKsync OS spec id.
 #[repr(u8)]
@@ -24,6 +31,11 @@ pub enum ZkSpecId {
 }
 
 impl ZkSpecId {
+    /// Returns the most recent [`ZkSpecId`].
+    pub const fn latest() -> Self {
+        Self::Atlas
+    }
+
     /// Converts the [`ZkSpecId`] into a [`SpecId`].
     pub const fn into_eth_spec(self) -> SpecId {
         match self {
@@ -35,6 +47,139 @@ impl ZkSpecId {
     pub const fn is_enabled_in(self, other: ZkSpecId) -> bool {
         other as u8 <= self as u8
     }
+
+    /// Returns the protocol version the [`ZkSpecId`] is deployed with on the settlement layer.
+    pub const fn to_network_version(self) -> u64 {
+        match self {
+            Self::Atlas => 25,
+        }
+    }
+
+    /// Returns the [`ZkSpecId`] deployed with the given protocol version.
+    pub const fn from_network_version(version: u64) -> Option<Self> {
+        let mut i = 0;
+        while i < ALL_SPECS.len() {
+            if ALL_SPECS[i].to_network_version() == version {
+                return Some(ALL_SPECS[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Checks if transactions of the given protocol version can be executed with the
+    /// [`ZkSpecId`].
+    pub const fn is_compatible_with_version(self, version: u64) -> bool {
+        match Self::from_network_version(version) {
+            Some(spec) => self.is_enabled_in(spec),
+            None => false,
+        }
+    }
+
+    /// Returns the range of Ethereum specs whose EVM rules the [`ZkSpecId`] executes.
+    pub const fn eth_spec_range(self) -> RangeInclusive<SpecId> {
+        match self {
+            Self::Atlas => SpecId::CANCUN..=SpecId::CANCUN,
+        }
+    }
+
+    /// Checks if the given Ethereum spec is in the [range](Self::eth_spec_range) of the
+    /// [`ZkSpecId`].
+    pub fn is_eth_spec_compatible(self, eth_spec: SpecId) -> bool {
+        self.eth_spec_range().contains(&eth_spec)
+    }
+
+    /// Returns all [`ZkSpecId`]s compatible with the given Ethereum spec, in activation order.
+    pub fn for_eth_spec(eth_spec: SpecId) -> Vec<Self> {
+        ALL_SPECS
+            .into_iter()
+            .filter(|spec| spec.is_eth_spec_compatible(eth_spec))
+            .collect()
+    }
+
+    /// Returns the most recent [`ZkSpecId`] whose EVM rules are enabled in the given
+    /// Ethereum spec, or `None` if the Ethereum spec predates all of them.
+    pub const fn closest_to_eth_spec(spec: SpecId) -> Option<Self> {
+        let mut closest = None;
+        let mut i = 0;
+        while i < ALL_SPECS.len() {
+            if spec.is_enabled_in(ALL_SPECS[i].into_eth_spec()) {
+                closest = Some(ALL_SPECS[i]);
+            }
+            i += 1;
+        }
+        closest
+    }
+
+    /// Default gas limit of an L2 block.
+    pub const fn default_block_gas_limit(self) -> u64 {
+        match self {
+            Self::Atlas => 80_000_000,
+        }
+    }
+
+    /// Default gas limit of a batch, the unit committed to L1.
+    pub const fn default_batch_gas_limit(self) -> u64 {
+        match self {
+            Self::Atlas => 1_000_000_000,
+        }
+    }
+
+    /// Default number of pubdata bytes a batch can publish.
+    pub const fn default_pubdata_bytes_per_batch(self) -> u32 {
+        match self {
+            Self::Atlas => MAX_PUBDATA_PER_BATCH as u32,
+        }
+    }
+
+    /// Default maximum number of transactions in a batch.
+    pub const fn default_max_transactions_per_batch(self) -> u32 {
+        match self {
+            Self::Atlas => 10_000,
+        }
+    }
+
+    /// Maximum number of L2 -> L1 messages a single transaction can send.
+    pub const fn max_l1_messages_per_tx(self) -> usize {
+        match self {
+            Self::Atlas => 16,
+        }
+    }
+
+    /// Returns the gas constants of the [`ZkSpecId`].
+    pub const fn gas_constants(self) -> ZkGasConstants {
+        match self {
+            Self::Atlas => ZkGasConstants {
+                tx_base_cost: 21_000,
+                bootloader_memory_overhead: BOOTLOADER_TX_ENCODING_OVERHEAD,
+                storage_write_pubdata_bytes: 64,
+            },
+        }
+    }
+}
+
+/// All spec ids, in activation order.
+const ALL_SPECS: [ZkSpecId; 1] = [ZkSpecId::Atlas];
+
+/// Per-spec gas constants of ZKsync OS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkGasConstants {
+    /// Base cost of every transaction.
+    pub tx_base_cost: u64,
+    /// Gas reserved for encoding the transaction into the bootloader memory.
+    pub bootloader_memory_overhead: u64,
+    /// Upper bound of the pubdata published for a single storage write: its key and value.
+    pub storage_write_pubdata_bytes: u64,
+}
+
+impl ZkGasConstants {
+    /// Intrinsic gas of a transaction with the given calldata: the base cost plus the
+    /// calldata token cost.
+    pub fn intrinsic_gas(&self, calldata: &[u8]) -> u64 {
+        let tokens = get_tokens_in_calldata(calldata, true);
+        self.tx_base_cost
+            .saturating_add(tokens.saturating_mul(STANDARD_TOKEN_COST))
+    }
 }
 
 impl From<ZkSpecId> for SpecId {
@@ -43,6 +188,32 @@ impl From<ZkSpecId> for SpecId {
     }
 }
 
+impl TryFrom<SpecId> for ZkSpecId {
+    type Error = TryFromSpecIdError;
+
+    fn try_from(eth_spec: SpecId) -> Result<Self, Self::Error> {
+        ALL_SPECS
+            .into_iter()
+            .find(|spec| spec.into_eth_spec() == eth_spec)
+            .ok_or(TryFromSpecIdError { eth_spec })
+    }
+}
+
+/// Error returned when an Ethereum [`SpecId`] has no ZKsync OS equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryFromSpecIdError {
+    /// Ethereum spec that failed to convert.
+    pub eth_spec: SpecId,
+}
+
+impl core::fmt::Display for TryFromSpecIdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "no ZKsync OS spec for Ethereum spec {}", self.eth_spec)
+    }
+}
+
+impl core::error::Error for TryFromSpecIdError {}
+
 impl FromStr for ZkSpecId {
     type Err = UnknownHardfork;
 
@@ -67,3 +238,99 @@ pub mod name {
     /// Initial spec name.
     pub const ATLAS: &str = "Atlas";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All spec ids, in activation order.
+    const SPECS: [ZkSpecId; 1] = [ZkSpecId::Atlas];
+
+    #[test]
+    fn is_enabled_in_matches_ordering() {
+        for (i, spec) in SPECS.into_iter().enumerate() {
+            for (j, other) in SPECS.into_iter().enumerate() {
+                assert_eq!(spec.is_enabled_in(other), j <= i, "{spec:?} in {other:?}");
+                assert_eq!(spec.is_enabled_in(other), other <= spec);
+            }
+        }
+    }
+
+    #[test]
+    fn latest_is_last() {
+        assert_eq!(SPECS.last(), Some(&ZkSpecId::latest()));
+    }
+
+    #[test]
+    fn default_limits() {
+        for spec in SPECS {
+            assert!(spec.default_block_gas_limit() > 0);
+            assert!(spec.default_batch_gas_limit() >= spec.default_block_gas_limit());
+            assert!(spec.default_pubdata_bytes_per_batch() > 0);
+            assert!(spec.default_max_transactions_per_batch() > 0);
+        }
+        for pair in SPECS.windows(2) {
+            let (older, newer) = (pair[0], pair[1]);
+            assert!(older.default_block_gas_limit() <= newer.default_block_gas_limit());
+            assert!(older.default_batch_gas_limit() <= newer.default_batch_gas_limit());
+            assert!(
+                older.default_pubdata_bytes_per_batch() <= newer.default_pubdata_bytes_per_batch()
+            );
+            assert!(
+                older.default_max_transactions_per_batch()
+                    <= newer.default_max_transactions_per_batch()
+            );
+        }
+    }
+
+    #[test]
+    fn network_version() {
+        assert_eq!(ZkSpecId::Atlas.to_network_version(), 25);
+        for spec in SPECS {
+            assert_eq!(
+                ZkSpecId::from_network_version(spec.to_network_version()),
+                Some(spec)
+            );
+            assert!(spec.is_compatible_with_version(spec.to_network_version()));
+        }
+        assert_eq!(ZkSpecId::from_network_version(24), None);
+        assert!(!ZkSpecId::latest().is_compatible_with_version(u64::MAX));
+    }
+
+    #[test]
+    fn eth_spec_conversion() {
+        for eth_spec in (0..=u8::MAX).filter_map(SpecId::try_from_u8) {
+            let converted = ZkSpecId::try_from(eth_spec);
+            if eth_spec == SpecId::CANCUN {
+                assert_eq!(converted, Ok(ZkSpecId::Atlas));
+            } else {
+                assert_eq!(converted, Err(TryFromSpecIdError { eth_spec }));
+            }
+
+            let closest = ZkSpecId::closest_to_eth_spec(eth_spec);
+            if eth_spec < SpecId::CANCUN {
+                assert_eq!(closest, None, "{eth_spec}");
+            } else {
+                assert_eq!(closest, Some(ZkSpecId::Atlas), "{eth_spec}");
+            }
+        }
+    }
+
+    #[test]
+    fn eth_spec_range() {
+        for spec in SPECS {
+            assert!(spec.eth_spec_range().contains(&spec.into_eth_spec()));
+            assert!(ZkSpecId::for_eth_spec(spec.into_eth_spec()).contains(&spec));
+        }
+        assert!(ZkSpecId::Atlas.is_eth_spec_compatible(SpecId::CANCUN));
+        assert!(!ZkSpecId::Atlas.is_eth_spec_compatible(SpecId::PRAGUE));
+        assert!(ZkSpecId::for_eth_spec(SpecId::SHANGHAI).is_empty());
+    }
+
+    #[test]
+    fn intrinsic_gas() {
+        let constants = ZkSpecId::Atlas.gas_constants();
+        assert_eq!(constants.intrinsic_gas(&[]), 21_000);
+        assert_eq!(constants.intrinsic_gas(&[0, 1]), 21_000 + 4 + 16);
+    }
+}
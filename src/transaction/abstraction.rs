@@ -2,16 +2,51 @@
 use super::priority_tx::{
     L1_PRIORITY_TRANSACTION_TYPE, L1ToL2TransactionParts, UPGRADE_TRANSACTION_TYPE,
 };
+use crate::{
+    ZkSpecId,
+    precompiles::l2_base_token::{
+        L2_BASE_TOKEN_ADDRESS, WITHDRAW_SELECTOR, WITHDRAW_WITH_MESSAGE_SELECTOR,
+    },
+};
 use auto_impl::auto_impl;
+use core::cmp::Ordering;
 use revm::{
     context::{
         TxEnv,
         tx::{TxEnvBuildError, TxEnvBuilder},
     },
-    context_interface::transaction::Transaction,
+    context_interface::transaction::{Transaction, TransactionType},
     handler::SystemCallTx,
+    interpreter::{
+        gas::{KECCAK256, KECCAK256WORD, calculate_initial_tx_gas_for_tx},
+        num_words,
+    },
     primitives::{Address, B256, Bytes, TxKind, U256},
 };
+use std::{collections::HashMap, string::String, vec::Vec};
+
+/// EIP-712 transaction type.
+pub const EIP712_TRANSACTION_TYPE: u8 = 0x71;
+
+/// Gas reserved for encoding the transaction into the bootloader memory.
+pub const BOOTLOADER_TX_ENCODING_OVERHEAD: u64 = 2_000;
+
+/// Number of storage writes assumed by [`ZKsyncTx::estimate_pubdata_bytes`].
+pub const DEFAULT_STORAGE_WRITE_ESTIMATE: u64 = 1;
+
+/// Computes the address of a contract deployed by `deployer` with `CREATE` at `nonce`.
+///
+/// ZKsync OS executes EVM bytecode natively, so the address is derived as on Ethereum.
+pub fn compute_address_for_create(deployer: Address, nonce: u64) -> Address {
+    deployer.create(nonce)
+}
+
+/// Computes the address of a contract deployed by `deployer` with `CREATE2`.
+///
+/// `bytecode_hash` is the keccak256 hash of the init code.
+pub fn compute_address_for_create2(deployer: Address, salt: B256, bytecode_hash: B256) -> Address {
+    deployer.create2(salt, bytecode_hash)
+}
 
 /// ZKsync OS Transaction trait.
 #[auto_impl(&, &mut, Box, Arc)]
@@ -28,6 +63,37 @@ pub trait ZkTxTr: Transaction {
     fn gas_used_override(&self) -> Option<u64>;
 
     fn force_fail(&self) -> bool;
+
+    /// Opaque `reservedDynamic` data of EIP-712 transactions.
+    fn reserved_dynamic(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Bytecodes published together with the transaction.
+    fn factory_deps(&self) -> &[Bytes] {
+        &[]
+    }
+
+    /// Whether the transaction is a system call. Only honored for transactions sent by the
+    /// bootloader.
+    fn is_system_call(&self) -> bool {
+        false
+    }
+
+    /// Gas needed to hash the factory deps of the transaction.
+    fn factory_deps_gas_cost(&self) -> u64 {
+        self.factory_deps()
+            .iter()
+            .map(|dep| {
+                KECCAK256.saturating_add(KECCAK256WORD.saturating_mul(num_words(dep.len()) as u64))
+            })
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Total size of the factory deps in bytes.
+    fn factory_deps_total_bytes(&self) -> usize {
+        self.factory_deps().iter().map(Bytes::len).sum()
+    }
 }
 
 /// ZKsync OS transaction.
@@ -41,6 +107,12 @@ pub struct ZKsyncTx<T: Transaction> {
     pub gas_used_override: Option<u64>,
     /// The execution status (success/revert) from the original ZKsync OS environment.
     pub force_fail: bool,
+    /// Opaque `reservedDynamic` data, only allowed for EIP-712 transactions.
+    pub reserved_dynamic: Option<Bytes>,
+    /// Bytecodes published together with the transaction.
+    pub factory_deps: Vec<Bytes>,
+    /// Whether the transaction is a system call sent by the bootloader.
+    pub is_system_call: bool,
 }
 
 impl<T: Transaction> AsRef<T> for ZKsyncTx<T> {
@@ -57,8 +129,82 @@ impl<T: Transaction> ZKsyncTx<T> {
             l1_to_l2_part: L1ToL2TransactionParts::default(),
             gas_used_override: None,
             force_fail: false,
+            reserved_dynamic: None,
+            factory_deps: Vec::new(),
+            is_system_call: false,
         }
     }
+
+    /// Returns `true` if this is an L1 -> L2 transaction with the mint and the refund
+    /// recipient set.
+    pub fn is_l1_to_l2_tx_well_formed(&self) -> bool {
+        self.is_l1_to_l2_tx()
+            && self.l1_to_l2_part.mint.is_some()
+            && self.l1_to_l2_part.refund_recipient.is_some()
+    }
+}
+
+impl<T: Transaction> ZKsyncTx<T> {
+    /// Estimates the initial gas of the transaction from its calldata alone.
+    ///
+    /// Covers the intrinsic gas (including the EIP-7623 floor), the factory deps hashing and
+    /// the bootloader encoding overhead, but not the gas spent on executing bytecode.
+    pub fn estimate_initial_gas(&self, spec: ZkSpecId) -> u64 {
+        let gas = calculate_initial_tx_gas_for_tx(self, spec.into_eth_spec());
+        gas.initial_gas
+            .saturating_add(self.factory_deps_gas_cost())
+            .max(gas.floor_gas)
+            .saturating_add(spec.gas_constants().bootloader_memory_overhead)
+    }
+
+    /// Estimates an upper bound of the pubdata the transaction publishes, assuming
+    /// [`DEFAULT_STORAGE_WRITE_ESTIMATE`] storage writes.
+    pub fn estimate_pubdata_bytes(&self, spec: ZkSpecId) -> u64 {
+        self.estimate_pubdata_bytes_with_storage_writes(spec, DEFAULT_STORAGE_WRITE_ESTIMATE)
+    }
+
+    /// Same as [`Self::estimate_pubdata_bytes`], but with the given number of storage writes.
+    ///
+    /// Messages sent to L1 are bounded by the calldata and deployed bytecodes by the factory
+    /// deps, so both are counted in full.
+    pub fn estimate_pubdata_bytes_with_storage_writes(
+        &self,
+        spec: ZkSpecId,
+        storage_writes: u64,
+    ) -> u64 {
+        let storage = spec
+            .gas_constants()
+            .storage_write_pubdata_bytes
+            .saturating_mul(storage_writes);
+        (self.input().len() as u64)
+            .saturating_add(self.factory_deps_total_bytes() as u64)
+            .saturating_add(storage)
+    }
+}
+
+impl<T: Transaction> ZKsyncTx<T> {
+    /// Compares the transactions by the order a mempool should execute them in at `basefee`.
+    ///
+    /// Transactions are grouped by caller, then ordered by ascending nonce, then by
+    /// descending effective gas price. [`Ordering::Less`] means `self` goes first.
+    pub fn priority_over(&self, other: &Self, basefee: u128) -> Ordering {
+        self.caller()
+            .cmp(&other.caller())
+            .then_with(|| self.nonce().cmp(&other.nonce()))
+            .then_with(|| {
+                other
+                    .effective_gas_price(basefee)
+                    .cmp(&self.effective_gas_price(basefee))
+            })
+    }
+
+    /// Returns `true` if `other` may replace `self` in a mempool: it has the same caller and
+    /// nonce and pays at least 10% more gas price.
+    pub fn is_replaceable_by(&self, other: &Self) -> bool {
+        self.caller() == other.caller()
+            && self.nonce() == other.nonce()
+            && other.gas_price().saturating_mul(10) >= self.gas_price().saturating_mul(11)
+    }
 }
 
 impl ZKsyncTx<TxEnv> {
@@ -66,6 +212,72 @@ impl ZKsyncTx<TxEnv> {
     pub fn builder() -> ZKsyncTxBuilder {
         ZKsyncTxBuilder::new()
     }
+
+    /// Creates `count` builders with consecutive nonces starting at `base_nonce`, e.g. to
+    /// submit transactions in parallel.
+    ///
+    /// Use [`ZKsyncTxBuilder::modify_base`] to set the other fields without overwriting the
+    /// nonce.
+    pub fn with_auto_nonce_range(base_nonce: u64, count: usize) -> Vec<ZKsyncTxBuilder> {
+        (base_nonce..)
+            .take(count)
+            .map(|nonce| Self::builder().modify_base(|base| base.nonce(nonce)))
+            .collect()
+    }
+
+    /// Wraps an Ethereum transaction, leaving the ZKsync OS specific fields empty.
+    pub fn from_eth_tx(tx: TxEnv) -> Self {
+        Self::new(tx)
+    }
+
+    /// Returns the Ethereum part of the transaction, e.g. for replaying it on a mainnet EVM.
+    pub fn to_eth_tx(&self) -> TxEnv {
+        self.base.clone()
+    }
+
+    /// Same as [`Self::to_eth_tx`], but if `strip_l1_fields` is set, transactions of types
+    /// unknown to Ethereum are converted into legacy transactions paying their effective gas
+    /// price at a zero base fee.
+    pub fn to_eth_tx_with_overrides(&self, strip_l1_fields: bool) -> TxEnv {
+        let mut tx = self.to_eth_tx();
+        if strip_l1_fields && TransactionType::from(tx.tx_type).is_custom() {
+            tx.gas_price = tx.effective_gas_price(0);
+            tx.gas_priority_fee = None;
+            tx.tx_type = TransactionType::Legacy as u8;
+        }
+        tx
+    }
+
+    /// Sets the gas limit to the [estimated initial gas](Self::estimate_initial_gas).
+    pub fn with_gas_limit_auto_estimated(mut self, spec: ZkSpecId) -> Self {
+        self.base.gas_limit = self.estimate_initial_gas(spec);
+        self
+    }
+
+    /// Returns a copy of the transaction with the given gas limit.
+    ///
+    /// The gas used override, if set, is capped at the new gas limit.
+    pub fn clone_with_new_gas_limit(&self, gas_limit: u64) -> Self {
+        let mut tx = self.clone();
+        tx.with_gas_limit_mut(gas_limit);
+        tx
+    }
+
+    /// Returns a copy of the transaction with the given gas used override.
+    pub fn clone_with_new_gas_used_override(&self, gas_used_override: Option<u64>) -> Self {
+        Self {
+            gas_used_override,
+            ..self.clone()
+        }
+    }
+
+    /// Sets the gas limit in place, capping the gas used override at it.
+    pub fn with_gas_limit_mut(&mut self, gas_limit: u64) {
+        self.base.gas_limit = gas_limit;
+        if let Some(gas_used_override) = &mut self.gas_used_override {
+            *gas_used_override = (*gas_used_override).min(gas_limit);
+        }
+    }
 }
 
 impl Default for ZKsyncTx<TxEnv> {
@@ -75,6 +287,9 @@ impl Default for ZKsyncTx<TxEnv> {
             l1_to_l2_part: L1ToL2TransactionParts::default(),
             gas_used_override: None,
             force_fail: false,
+            reserved_dynamic: None,
+            factory_deps: Vec::new(),
+            is_system_call: false,
         }
     }
 }
@@ -192,6 +407,18 @@ impl<T: Transaction> ZkTxTr for ZKsyncTx<T> {
     fn force_fail(&self) -> bool {
         self.force_fail
     }
+
+    fn reserved_dynamic(&self) -> Option<&[u8]> {
+        self.reserved_dynamic.as_deref()
+    }
+
+    fn factory_deps(&self) -> &[Bytes] {
+        &self.factory_deps
+    }
+
+    fn is_system_call(&self) -> bool {
+        self.is_system_call
+    }
 }
 
 /// Builder for constructing [`ZKsyncTx`] instances
@@ -201,6 +428,9 @@ pub struct ZKsyncTxBuilder {
     l1_to_l2_part: L1ToL2TransactionParts,
     gas_used_override: Option<u64>,
     force_fail: bool,
+    reserved_dynamic: Option<Bytes>,
+    factory_deps: Vec<Bytes>,
+    is_system_call: bool,
 }
 
 impl ZKsyncTxBuilder {
@@ -211,6 +441,9 @@ impl ZKsyncTxBuilder {
             l1_to_l2_part: L1ToL2TransactionParts::default(),
             gas_used_override: None,
             force_fail: false,
+            reserved_dynamic: None,
+            factory_deps: Vec::new(),
+            is_system_call: false,
         }
     }
 
@@ -220,6 +453,12 @@ impl ZKsyncTxBuilder {
         self
     }
 
+    /// Modifies the base transaction builder in place, keeping the fields already set.
+    pub fn modify_base(mut self, f: impl FnOnce(TxEnvBuilder) -> TxEnvBuilder) -> Self {
+        self.base = f(self.base);
+        self
+    }
+
     /// Set the mint of the L1 -> L2 part of the transaction.
     pub fn mint(mut self, mint: U256) -> Self {
         self.l1_to_l2_part.mint = Some(mint);
@@ -246,6 +485,112 @@ impl ZKsyncTxBuilder {
         self
     }
 
+    /// Set the `reservedDynamic` data of an EIP-712 transaction.
+    pub fn reserved_dynamic(mut self, reserved_dynamic: Option<Bytes>) -> Self {
+        self.reserved_dynamic = reserved_dynamic;
+        self
+    }
+
+    /// Marks the transaction as a system call. Only honored for transactions sent by the
+    /// bootloader, which are then neither charged nor bump its nonce.
+    pub fn mark_as_system_call(mut self, is_system: bool) -> Self {
+        self.is_system_call = is_system;
+        self
+    }
+
+    /// Set the factory deps published together with the transaction.
+    pub fn factory_deps(mut self, factory_deps: Vec<Bytes>) -> Self {
+        self.factory_deps = factory_deps;
+        self
+    }
+
+    /// Calls `L2BaseToken.withdraw(l1_recipient)` sending `amount`, which is withdrawn to
+    /// `l1_recipient` on L1.
+    pub fn with_calldata_withdraw(mut self, l1_recipient: Address, amount: U256) -> Self {
+        let mut data = WITHDRAW_SELECTOR.to_vec();
+        data.extend_from_slice(l1_recipient.into_word().as_slice());
+        self.base = self
+            .base
+            .kind(TxKind::Call(L2_BASE_TOKEN_ADDRESS))
+            .value(amount)
+            .data(data.into());
+        self
+    }
+
+    /// Same as [`Self::with_calldata_withdraw`], but calls
+    /// `L2BaseToken.withdrawWithMessage(l1_recipient, message)`, passing `message` to the L1
+    /// recipient.
+    pub fn with_calldata_withdraw_with_message(
+        mut self,
+        l1_recipient: Address,
+        amount: U256,
+        message: &[u8],
+    ) -> Self {
+        let mut data = WITHDRAW_WITH_MESSAGE_SELECTOR.to_vec();
+        data.extend_from_slice(l1_recipient.into_word().as_slice());
+        // Offset of the message, right after the two head words.
+        data.extend_from_slice(&U256::from(64).to_be_bytes::<32>());
+        data.extend_from_slice(&U256::from(message.len()).to_be_bytes::<32>());
+        data.extend_from_slice(message);
+        data.resize(data.len() + (32 - message.len() % 32) % 32, 0);
+        self.base = self
+            .base
+            .kind(TxKind::Call(L2_BASE_TOKEN_ADDRESS))
+            .value(amount)
+            .data(data.into());
+        self
+    }
+
+    /// Marks the transaction as an L1 -> L2 priority transaction paying `l1_gas_price`
+    /// with no priority fee, as submitted through the L1 contract.
+    pub fn apply_l1_to_l2_defaults(mut self, l1_gas_price: u128) -> Self {
+        self.base = self
+            .base
+            .tx_type(Some(L1_PRIORITY_TRANSACTION_TYPE))
+            .gas_price(l1_gas_price)
+            .gas_priority_fee(Some(0));
+        self
+    }
+
+    /// Parses a builder from human readable key-value pairs, e.g. from a config file.
+    ///
+    /// Addresses, `data`, `value` and `mint` are hex encoded, `tx_type` is a `0x` prefixed
+    /// hex byte and the remaining numbers are decimal.
+    pub fn from_user_input(input: &HashMap<&str, &str>) -> Result<Self, ZkUserInputError> {
+        let mut builder = Self::new();
+        let mut base = TxEnvBuilder::new();
+        for (&field, &value) in input {
+            let invalid = || ZkUserInputError::InvalidValue {
+                field: field.into(),
+                value: value.into(),
+            };
+            match field {
+                "from" => base = base.caller(value.parse().map_err(|_| invalid())?),
+                "to" => base = base.kind(TxKind::Call(value.parse().map_err(|_| invalid())?)),
+                "value" => base = base.value(value.parse().map_err(|_| invalid())?),
+                "data" => base = base.data(value.parse().map_err(|_| invalid())?),
+                "gas_limit" => base = base.gas_limit(value.parse().map_err(|_| invalid())?),
+                "gas_price" => base = base.gas_price(value.parse().map_err(|_| invalid())?),
+                "nonce" => base = base.nonce(value.parse().map_err(|_| invalid())?),
+                "chain_id" => base = base.chain_id(Some(value.parse().map_err(|_| invalid())?)),
+                "tx_type" => {
+                    let tx_type = value
+                        .strip_prefix("0x")
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                        .ok_or_else(invalid)?;
+                    base = base.tx_type(Some(tx_type));
+                }
+                "mint" => builder = builder.mint(value.parse().map_err(|_| invalid())?),
+                "refund_recipient" => {
+                    builder = builder.refund_recipient(Some(value.parse().map_err(|_| invalid())?))
+                }
+                "force_fail" => builder = builder.force_fail(value.parse().map_err(|_| invalid())?),
+                _ => return Err(ZkUserInputError::UnknownField(field.into())),
+            }
+        }
+        Ok(builder.base(base))
+    }
+
     /// Build the [`ZKsyncTx`] with default values for missing fields.
     ///
     /// This is useful for testing and debugging where it is not necessary to
@@ -260,6 +605,9 @@ impl ZKsyncTxBuilder {
             l1_to_l2_part: self.l1_to_l2_part,
             gas_used_override: self.gas_used_override,
             force_fail: self.force_fail,
+            reserved_dynamic: self.reserved_dynamic,
+            factory_deps: self.factory_deps,
+            is_system_call: self.is_system_call,
         }
     }
 
@@ -273,6 +621,9 @@ impl ZKsyncTxBuilder {
             l1_to_l2_part: self.l1_to_l2_part,
             gas_used_override: self.gas_used_override,
             force_fail: self.force_fail,
+            reserved_dynamic: self.reserved_dynamic,
+            factory_deps: self.factory_deps,
+            is_system_call: self.is_system_call,
         })
     }
 }
@@ -292,14 +643,343 @@ impl From<TxEnvBuildError> for ZkBuilderror {
     }
 }
 
+/// Error returned by [`ZKsyncTxBuilder::from_user_input`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZkUserInputError {
+    /// The field is not a transaction field.
+    UnknownField(String),
+    /// The value could not be parsed for the field.
+    InvalidValue {
+        /// Name of the field.
+        field: String,
+        /// Value that failed to parse.
+        value: String,
+    },
+}
+
+impl core::fmt::Display for ZkUserInputError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownField(field) => write!(f, "unknown transaction field {field}"),
+            Self::InvalidValue { field, value } => {
+                write!(f, "invalid value {value:?} for transaction field {field}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ZkUserInputError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{DefaultZk, ZKsyncTxError, api::builder::ZkBuilder};
+    use core::str::FromStr;
     use revm::{
-        context_interface::Transaction,
-        primitives::{Address, B256},
+        Context, ExecuteCommitEvm, ExecuteEvm,
+        context_interface::{Transaction, result::EVMError},
+        database::{CacheDB, EmptyDB},
+        primitives::{Address, B256, hex, keccak256},
+        state::AccountInfo,
     };
 
+    #[test]
+    fn auto_nonce_range() {
+        let caller = Address::with_last_byte(0x42);
+        let txs: Vec<_> = ZKsyncTx::with_auto_nonce_range(0, 5)
+            .into_iter()
+            .map(|builder| builder.modify_base(|base| base.caller(caller)).build_fill())
+            .collect();
+        assert_eq!(
+            txs.iter().map(|tx| tx.nonce()).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+
+        let mut evm = Context::default()
+            .with_db(CacheDB::<EmptyDB>::default())
+            .build_zk();
+        for tx in txs {
+            assert!(evm.transact_commit(tx).unwrap().is_success());
+        }
+    }
+
+    #[test]
+    fn withdraw_calldata() {
+        let caller = Address::with_last_byte(0x42);
+        let l1_recipient = Address::repeat_byte(0x11);
+        let amount = U256::from(100);
+        let withdraw = ZKsyncTx::builder()
+            .base(TxEnv::builder().caller(caller))
+            .with_calldata_withdraw(l1_recipient, amount)
+            .build_fill();
+        assert_eq!(withdraw.kind(), TxKind::Call(L2_BASE_TOKEN_ADDRESS));
+        assert_eq!(withdraw.value(), amount);
+        assert_eq!(
+            withdraw.input()[..],
+            hex!(
+                "51cff8d9"
+                "0000000000000000000000001111111111111111111111111111111111111111"
+            )
+        );
+
+        let with_message = ZKsyncTx::builder()
+            .base(TxEnv::builder().caller(caller).nonce(1))
+            .with_calldata_withdraw_with_message(l1_recipient, amount, &[0xab; 33])
+            .build_fill();
+        let mut expected = hex!(
+            "84bc3eb0"
+            "0000000000000000000000001111111111111111111111111111111111111111"
+            "0000000000000000000000000000000000000000000000000000000000000040"
+            "0000000000000000000000000000000000000000000000000000000000000021"
+        )
+        .to_vec();
+        expected.extend_from_slice(&[0xab; 33]);
+        expected.extend_from_slice(&[0; 31]);
+        assert_eq!(with_message.input()[..], expected);
+
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000)));
+        let mut evm = Context::default().with_db(db).build_zk();
+        assert!(evm.transact_commit(withdraw).unwrap().is_success());
+        assert!(evm.transact_commit(with_message).unwrap().is_success());
+    }
+
+    #[test]
+    fn mempool_priority() {
+        let tx = |caller: u8, nonce: u64, gas_price: u128| {
+            ZKsyncTx::builder()
+                .base(
+                    TxEnv::builder()
+                        .caller(Address::with_last_byte(caller))
+                        .nonce(nonce)
+                        .gas_price(gas_price),
+                )
+                .build_fill()
+        };
+
+        // Callers are compared first, then nonces, then gas prices.
+        assert_eq!(tx(1, 5, 1).priority_over(&tx(2, 0, 100), 0), Ordering::Less);
+        assert_eq!(tx(1, 0, 1).priority_over(&tx(1, 1, 100), 0), Ordering::Less);
+        assert_eq!(tx(1, 0, 100).priority_over(&tx(1, 0, 1), 0), Ordering::Less);
+        assert_eq!(
+            tx(1, 0, 1).priority_over(&tx(1, 0, 100), 0),
+            Ordering::Greater
+        );
+        assert_eq!(
+            tx(1, 0, 10).priority_over(&tx(1, 0, 10), 0),
+            Ordering::Equal
+        );
+
+        assert!(tx(1, 0, 100).is_replaceable_by(&tx(1, 0, 110)));
+        assert!(!tx(1, 0, 100).is_replaceable_by(&tx(1, 0, 109)));
+        assert!(!tx(1, 0, 100).is_replaceable_by(&tx(1, 1, 200)));
+        assert!(!tx(1, 0, 100).is_replaceable_by(&tx(2, 0, 200)));
+    }
+
+    #[test]
+    fn reserved_dynamic_requires_eip712_type() {
+        let tx = ZKsyncTx::builder()
+            .reserved_dynamic(Some(Bytes::from_static(&[1, 2, 3])))
+            .build_fill();
+        assert_eq!(tx.reserved_dynamic(), Some(&[1u8, 2, 3][..]));
+
+        let mut evm = Context::default().build_zk();
+        assert!(matches!(
+            evm.transact(tx),
+            Err(EVMError::Transaction(ZKsyncTxError::UnsupportedTxField(_)))
+        ));
+    }
+
+    #[test]
+    fn factory_deps_gas_cost() {
+        let tx = ZKsyncTx::builder().build_fill();
+        assert_eq!(tx.factory_deps_gas_cost(), 0);
+        assert_eq!(tx.factory_deps_total_bytes(), 0);
+
+        let tx = ZKsyncTx::builder()
+            .factory_deps(vec![Bytes::from(vec![0; 33])])
+            .build_fill();
+        assert_eq!(tx.factory_deps_gas_cost(), KECCAK256 + 2 * KECCAK256WORD);
+        assert_eq!(tx.factory_deps_total_bytes(), 33);
+
+        let deps = [0, 1, 32, 64, 100]
+            .into_iter()
+            .map(|len| Bytes::from(vec![0; len]))
+            .collect();
+        let tx = ZKsyncTx::builder().factory_deps(deps).build_fill();
+        assert_eq!(
+            tx.factory_deps_gas_cost(),
+            5 * KECCAK256 + (1 + 1 + 2 + 4) * KECCAK256WORD
+        );
+        assert_eq!(tx.factory_deps_total_bytes(), 197);
+    }
+
+    #[test]
+    fn factory_deps_require_gas() {
+        // Covers the ZKsync OS minimum, but not the creation cost on top of the deps hashing.
+        let tx = ZKsyncTx::builder()
+            .base(TxEnv::builder().gas_limit(53_000).kind(TxKind::Create))
+            .factory_deps(vec![Bytes::from(vec![0; 32])])
+            .build_fill();
+
+        let mut evm = Context::default().build_zk();
+        assert_eq!(
+            evm.transact(tx).unwrap_err(),
+            EVMError::Transaction(ZKsyncTxError::InsufficientGasForFactoryDeps {
+                required: 53_000 + KECCAK256 + KECCAK256WORD,
+                available: 53_000,
+            })
+        );
+    }
+
+    #[test]
+    fn auto_estimated_gas_limit_covers_transfer() {
+        let tx = ZKsyncTx::builder()
+            .build_fill()
+            .with_gas_limit_auto_estimated(ZkSpecId::Atlas);
+        let gas_limit = tx.gas_limit();
+
+        let mut evm = Context::default().build_zk();
+        let result = evm.transact(tx).unwrap().result;
+        assert!(result.is_success());
+        assert!(gas_limit <= result.gas_used() * 6 / 5);
+    }
+
+    #[test]
+    fn create_addresses() {
+        let deployer = Address::from_str("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0").unwrap();
+        assert_eq!(
+            compute_address_for_create(deployer, 0),
+            Address::from_str("0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d").unwrap()
+        );
+        assert_eq!(
+            compute_address_for_create(deployer, 1),
+            Address::from_str("0x343c43a37d37dff08ae8c4a11544c718abb4fcf8").unwrap()
+        );
+
+        // EIP-1014 example 0.
+        assert_eq!(
+            compute_address_for_create2(Address::ZERO, B256::ZERO, keccak256([0x00])),
+            Address::from_str("0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38").unwrap()
+        );
+    }
+
+    #[test]
+    fn clone_with_new_gas_limit() {
+        let tx = ZKsyncTx::builder()
+            .base(TxEnv::builder().gas_limit(100_000))
+            .gas_used_override(Some(60_000))
+            .build_fill();
+
+        let lower = tx.clone_with_new_gas_limit(50_000);
+        assert_eq!(lower.gas_limit(), 50_000);
+        assert_eq!(lower.gas_used_override, Some(50_000));
+
+        let higher = tx.clone_with_new_gas_limit(200_000);
+        assert_eq!(higher.gas_limit(), 200_000);
+        assert_eq!(higher.gas_used_override, Some(60_000));
+
+        let no_override = tx.clone_with_new_gas_used_override(None);
+        assert_eq!(no_override.gas_used_override, None);
+        assert_eq!(no_override.gas_limit(), 100_000);
+    }
+
+    #[test]
+    fn eth_tx_round_trip() {
+        let tx = ZKsyncTx::builder()
+            .base(TxEnv::builder().gas_limit(50_000).gas_price(7).nonce(3))
+            .build_fill();
+        assert_eq!(ZKsyncTx::from_eth_tx(tx.to_eth_tx()), tx);
+        assert_eq!(tx.to_eth_tx_with_overrides(true), tx.to_eth_tx());
+
+        let eip712 = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .tx_type(Some(EIP712_TRANSACTION_TYPE))
+                    .gas_price(100)
+                    .gas_priority_fee(Some(5)),
+            )
+            .build_fill();
+        let eth = eip712.to_eth_tx_with_overrides(true);
+        assert_eq!(eth.tx_type, TransactionType::Legacy as u8);
+        assert_eq!(eth.gas_price, 5);
+        assert_eq!(eth.gas_priority_fee, None);
+    }
+
+    #[test]
+    fn from_user_input() {
+        let input = HashMap::from([
+            ("from", "0x0000000000000000000000000000000000000001"),
+            ("to", "0x0000000000000000000000000000000000000002"),
+            ("value", "0x64"),
+            ("data", "0xdeadbeef"),
+            ("gas_limit", "100000"),
+            ("gas_price", "250000000"),
+            ("nonce", "7"),
+            ("chain_id", "270"),
+            ("mint", "0x3e8"),
+            (
+                "refund_recipient",
+                "0x0000000000000000000000000000000000000003",
+            ),
+            ("tx_type", "0x7f"),
+            ("force_fail", "false"),
+        ]);
+        let expected = ZKsyncTx::builder()
+            .base(
+                TxEnv::builder()
+                    .caller(Address::with_last_byte(1))
+                    .kind(TxKind::Call(Address::with_last_byte(2)))
+                    .value(U256::from(100))
+                    .data(Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]))
+                    .gas_limit(100_000)
+                    .gas_price(250_000_000)
+                    .nonce(7)
+                    .chain_id(Some(270))
+                    .tx_type(Some(L1_PRIORITY_TRANSACTION_TYPE)),
+            )
+            .mint(U256::from(1_000))
+            .refund_recipient(Some(Address::with_last_byte(3)))
+            .force_fail(false)
+            .build_fill();
+        let parsed = ZKsyncTxBuilder::from_user_input(&input)
+            .unwrap()
+            .build_fill();
+        assert_eq!(parsed, expected);
+        assert!(parsed.is_l1_to_l2_tx_well_formed());
+
+        assert_eq!(
+            ZKsyncTxBuilder::from_user_input(&HashMap::from([("gas", "1")])).unwrap_err(),
+            ZkUserInputError::UnknownField("gas".into())
+        );
+        assert_eq!(
+            ZKsyncTxBuilder::from_user_input(&HashMap::from([("tx_type", "127")])).unwrap_err(),
+            ZkUserInputError::InvalidValue {
+                field: "tx_type".into(),
+                value: "127".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn l1_to_l2_defaults() {
+        let tx = ZKsyncTx::builder()
+            .mint(U256::from(1_000))
+            .apply_l1_to_l2_defaults(100)
+            .build_fill();
+        assert!(!tx.is_l1_to_l2_tx_well_formed());
+
+        let tx = ZKsyncTx::builder()
+            .mint(U256::from(1_000))
+            .refund_recipient(Some(Address::with_last_byte(0x42)))
+            .apply_l1_to_l2_defaults(100)
+            .build_fill();
+        assert!(tx.is_l1_to_l2_tx_well_formed());
+        assert_eq!(tx.tx_type(), L1_PRIORITY_TRANSACTION_TYPE);
+        assert_eq!(tx.max_fee_per_gas(), 100);
+        assert_eq!(tx.max_priority_fee_per_gas(), Some(0));
+    }
+
     // #[test]
     // fn test_deposit_transaction_fields() {
     //     let base_tx = TxEnv::builder()
@@ -1,15 +1,210 @@
 //! Contains the `[ZKsyncTxError]` type.
+use crate::ZkHaltReason;
 use core::fmt::Display;
-use revm::context_interface::{
-    result::{EVMError, InvalidTransaction},
-    transaction::TransactionError,
+use revm::{
+    context_interface::{
+        result::{EVMError, HaltReason, InvalidTransaction},
+        transaction::TransactionError,
+    },
+    primitives::U256,
 };
+use std::string::String;
 
 /// ZKsync OS transaction validation error.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum ZKsyncTxError {
     /// Base transaction error.
     Base(InvalidTransaction),
+    /// Execution reverted with an `Error(string)` reason.
+    Revert(String),
+    /// The transaction sets a field its type does not support.
+    UnsupportedTxField(String),
+    /// The gas limit does not cover the initial gas and the factory deps hashing.
+    InsufficientGasForFactoryDeps {
+        /// Initial gas including the factory deps hashing.
+        required: u64,
+        /// Gas limit of the transaction.
+        available: u64,
+    },
+    /// The gas limit is below the minimum gas limit of the transaction.
+    GasLimitTooLow {
+        /// Gas limit of the transaction.
+        provided: u64,
+        /// Minimum gas limit, see [`crate::handler::minimum_gas_limit`].
+        minimum: u64,
+    },
+    /// The pubdata published by the transaction does not fit into a batch.
+    WouldExceedBatchPubdataLimit {
+        /// Pubdata bytes published by the transaction.
+        required: u64,
+        /// Pubdata limit of a batch.
+        limit: u64,
+    },
+    /// The value of an L1 -> L2 transaction exceeds the amount it mints.
+    MintInsufficientForValue {
+        /// Amount minted on L2.
+        mint: U256,
+        /// Value transferred by the call.
+        value: U256,
+    },
+}
+
+/// Selector of the Solidity `Error(string)` revert reason.
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+impl ZKsyncTxError {
+    /// Maps a halt reason that corresponds to an invalid transaction to its error.
+    pub fn from_halt_reason(reason: &ZkHaltReason) -> Option<Self> {
+        let error = match reason {
+            ZkHaltReason::Base(HaltReason::NonceOverflow) => {
+                InvalidTransaction::NonceOverflowInTransaction
+            }
+            ZkHaltReason::Base(HaltReason::OverflowPayment) => {
+                InvalidTransaction::OverflowPaymentInTransaction
+            }
+            ZkHaltReason::Base(HaltReason::CreateInitCodeSizeLimit) => {
+                InvalidTransaction::CreateInitCodeSizeLimit
+            }
+            _ => return None,
+        };
+        Some(Self::Base(error))
+    }
+
+    /// Decodes an ABI encoded `Error(string)` revert reason.
+    pub fn from_revert_bytes(bytes: &[u8]) -> Option<Self> {
+        let data = bytes.strip_prefix(&ERROR_STRING_SELECTOR)?;
+        if data.len() < 64 || U256::from_be_slice(&data[..32]) != U256::from(32) {
+            return None;
+        }
+        let length: usize = U256::from_be_slice(&data[32..64]).try_into().ok()?;
+        let message = data.get(64..64usize.checked_add(length)?)?;
+        let message = core::str::from_utf8(message).ok()?;
+        Some(Self::Revert(message.into()))
+    }
+
+    /// Returns `true` if resubmitting the same transaction later may succeed.
+    ///
+    /// Only nonce collisions qualify: a nonce ahead of the account becomes valid once the
+    /// preceding transactions are included, while a used nonce never becomes valid again.
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, Self::Base(InvalidTransaction::NonceTooHigh { .. }))
+    }
+
+    /// Returns the expected nonce if the transaction was rejected for its nonce.
+    pub fn recoverable_nonce(&self) -> Option<NonceSuggestion> {
+        match self {
+            Self::Base(InvalidTransaction::NonceTooHigh { state, .. }) => {
+                Some(NonceSuggestion::TooHigh { expected: *state })
+            }
+            Self::Base(InvalidTransaction::NonceTooLow { state, .. }) => {
+                Some(NonceSuggestion::TooLow { expected: *state })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error can only be raised for L1 -> L2 transactions.
+    pub fn is_l1_to_l2_specific(&self) -> bool {
+        match self {
+            Self::Base(_)
+            | Self::Revert(_)
+            | Self::UnsupportedTxField(_)
+            | Self::InsufficientGasForFactoryDeps { .. }
+            | Self::GasLimitTooLow { .. }
+            | Self::WouldExceedBatchPubdataLimit { .. } => false,
+            Self::MintInsufficientForValue { .. } => true,
+        }
+    }
+
+    /// Returns `true` if the error can only be raised for L2 transactions.
+    ///
+    /// L1 -> L2 transactions are pre-verified on the settlement layer and skip the environment,
+    /// nonce and balance checks.
+    pub fn is_l2_native(&self) -> bool {
+        match self {
+            Self::Base(
+                InvalidTransaction::CallGasCostMoreThanGasLimit { .. }
+                | InvalidTransaction::GasFloorMoreThanGasLimit { .. }
+                | InvalidTransaction::OverflowPaymentInTransaction,
+            ) => false,
+            Self::Base(_)
+            | Self::UnsupportedTxField(_)
+            | Self::GasLimitTooLow { .. }
+            | Self::WouldExceedBatchPubdataLimit { .. } => true,
+            Self::Revert(_)
+            | Self::InsufficientGasForFactoryDeps { .. }
+            | Self::MintInsufficientForValue { .. } => false,
+        }
+    }
+
+    /// Returns how much gas has to be added to the gas limit to get past the error, if the
+    /// error is caused by a too low gas limit.
+    pub fn estimated_gas_needed(&self) -> Option<u64> {
+        match self {
+            Self::GasLimitTooLow { provided, minimum } => Some(minimum.saturating_sub(*provided)),
+            Self::InsufficientGasForFactoryDeps {
+                required,
+                available,
+            } => Some(required.saturating_sub(*available)),
+            _ => None,
+        }
+    }
+
+    /// Suggests a new gas limit: the `current_limit` increased by the
+    /// [needed gas](Self::estimated_gas_needed), plus a 10% safety margin.
+    pub fn suggest_gas_limit(&self, current_limit: u64) -> Option<u64> {
+        let required = current_limit.saturating_add(self.estimated_gas_needed()?);
+        Some(required.saturating_add(required.div_ceil(10)))
+    }
+
+    /// Returns how the error should be handled by the mempool and RPC layers.
+    pub fn severity(&self) -> ErrorSeverity {
+        if self.is_retriable() {
+            ErrorSeverity::Transient
+        } else if matches!(self, Self::UnsupportedTxField(_)) {
+            ErrorSeverity::Critical
+        } else {
+            ErrorSeverity::Invalid
+        }
+    }
+}
+
+/// Nonce mismatch reported by [`ZKsyncTxError::recoverable_nonce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NonceSuggestion {
+    /// The nonce is ahead of the account, another transaction is likely pending. The
+    /// transaction should be requeued.
+    TooHigh {
+        /// Nonce of the account.
+        expected: u64,
+    },
+    /// The nonce was already used. The transaction should be dropped or resigned.
+    TooLow {
+        /// Nonce of the account.
+        expected: u64,
+    },
+}
+
+impl NonceSuggestion {
+    /// Returns the nonce to use instead of `current_nonce`: the account nonce, unless
+    /// `current_nonce` is already past it.
+    pub fn suggested_nonce(&self, current_nonce: u64) -> u64 {
+        match self {
+            Self::TooHigh { expected } => *expected,
+            Self::TooLow { expected } => current_nonce.max(*expected),
+        }
+    }
+}
+
+/// Severity of a [`ZKsyncTxError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorSeverity {
+    /// Malformed transaction, the peer that sent it should be disconnected.
+    Critical,
+    /// Invalid transaction that should be rejected.
+    Invalid,
+    /// Transaction that may become valid later, retry is possible.
+    Transient,
 }
 
 impl TransactionError for ZKsyncTxError {}
@@ -18,6 +213,27 @@ impl Display for ZKsyncTxError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Base(error) => error.fmt(f),
+            Self::Revert(reason) => write!(f, "execution reverted: {reason}"),
+            Self::UnsupportedTxField(field) => {
+                write!(f, "field {field} is not supported by the transaction type")
+            }
+            Self::InsufficientGasForFactoryDeps {
+                required,
+                available,
+            } => write!(
+                f,
+                "gas limit {available} is less than {required} required to hash factory deps"
+            ),
+            Self::GasLimitTooLow { provided, minimum } => {
+                write!(f, "gas limit {provided} is below the minimum of {minimum}")
+            }
+            Self::WouldExceedBatchPubdataLimit { required, limit } => write!(
+                f,
+                "transaction publishes {required} bytes of pubdata, more than the batch limit of {limit}"
+            ),
+            Self::MintInsufficientForValue { mint, value } => {
+                write!(f, "value {value} exceeds the minted amount {mint}")
+            }
         }
     }
 }
@@ -41,6 +257,46 @@ mod test {
     use super::*;
     use std::string::ToString;
 
+    #[test]
+    fn test_suggest_gas_limit() {
+        let too_low = ZKsyncTxError::GasLimitTooLow {
+            provided: 20_000,
+            minimum: 21_000,
+        };
+        assert_eq!(too_low.estimated_gas_needed(), Some(1_000));
+        assert_eq!(too_low.suggest_gas_limit(20_000), Some(23_100));
+
+        let factory_deps = ZKsyncTxError::InsufficientGasForFactoryDeps {
+            required: 21_036,
+            available: 21_000,
+        };
+        assert_eq!(factory_deps.estimated_gas_needed(), Some(36));
+        assert!(factory_deps.suggest_gas_limit(21_000).unwrap() > 21_036);
+
+        let revert = ZKsyncTxError::Revert("fail".into());
+        assert_eq!(revert.estimated_gas_needed(), None);
+        assert_eq!(revert.suggest_gas_limit(21_000), None);
+    }
+
+    #[test]
+    fn test_recoverable_nonce() {
+        let too_high = ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 5, state: 3 });
+        let suggestion = too_high.recoverable_nonce().unwrap();
+        assert_eq!(suggestion, NonceSuggestion::TooHigh { expected: 3 });
+        assert_eq!(suggestion.suggested_nonce(5), 3);
+
+        let too_low = ZKsyncTxError::Base(InvalidTransaction::NonceTooLow { tx: 1, state: 3 });
+        let suggestion = too_low.recoverable_nonce().unwrap();
+        assert_eq!(suggestion, NonceSuggestion::TooLow { expected: 3 });
+        assert_eq!(suggestion.suggested_nonce(1), 3);
+        assert_eq!(suggestion.suggested_nonce(4), 4);
+
+        assert_eq!(
+            ZKsyncTxError::Revert("fail".into()).recoverable_nonce(),
+            None
+        );
+    }
+
     #[test]
     fn test_display_zk_errors() {
         assert_eq!(
@@ -48,4 +304,83 @@ mod test {
             "nonce 2 too high, expected 1"
         );
     }
+
+    #[test]
+    fn test_from_halt_reason() {
+        assert_eq!(
+            ZKsyncTxError::from_halt_reason(&ZkHaltReason::Base(HaltReason::NonceOverflow)),
+            Some(ZKsyncTxError::Base(
+                InvalidTransaction::NonceOverflowInTransaction
+            ))
+        );
+        assert_eq!(
+            ZKsyncTxError::from_halt_reason(&ZkHaltReason::Base(HaltReason::InvalidJump)),
+            None
+        );
+        assert_eq!(
+            ZKsyncTxError::from_halt_reason(&ZkHaltReason::FailedDeposit),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_revert_bytes() {
+        let mut bytes = ERROR_STRING_SELECTOR.to_vec();
+        bytes.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        bytes.extend_from_slice(&U256::from(4).to_be_bytes::<32>());
+        bytes.extend_from_slice(&[b'f', b'a', b'i', b'l']);
+        bytes.extend_from_slice(&[0; 28]);
+        assert_eq!(
+            ZKsyncTxError::from_revert_bytes(&bytes),
+            Some(ZKsyncTxError::Revert("fail".into()))
+        );
+
+        assert_eq!(ZKsyncTxError::from_revert_bytes(&bytes[..40]), None);
+        assert_eq!(ZKsyncTxError::from_revert_bytes(&bytes[4..]), None);
+    }
+
+    #[test]
+    fn test_is_retriable() {
+        assert!(
+            ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 })
+                .is_retriable()
+        );
+        assert!(
+            !ZKsyncTxError::Base(InvalidTransaction::NonceTooLow { tx: 1, state: 2 })
+                .is_retriable()
+        );
+        assert!(!ZKsyncTxError::Base(InvalidTransaction::GasPriceLessThanBasefee).is_retriable());
+        assert!(!ZKsyncTxError::Base(InvalidTransaction::InvalidChainId).is_retriable());
+        assert!(!ZKsyncTxError::Revert("fail".into()).is_retriable());
+    }
+
+    #[test]
+    fn test_classification() {
+        let nonce = ZKsyncTxError::Base(InvalidTransaction::NonceTooLow { tx: 1, state: 2 });
+        assert!(nonce.is_l2_native());
+        assert!(!nonce.is_l1_to_l2_specific());
+        assert_eq!(nonce.severity(), ErrorSeverity::Invalid);
+        let pending = ZKsyncTxError::Base(InvalidTransaction::NonceTooHigh { tx: 2, state: 1 });
+        assert_eq!(pending.severity(), ErrorSeverity::Transient);
+
+        let gas = ZKsyncTxError::Base(InvalidTransaction::CallGasCostMoreThanGasLimit {
+            initial_gas: 21_000,
+            gas_limit: 20_000,
+        });
+        assert!(!gas.is_l2_native());
+        assert!(!gas.is_l1_to_l2_specific());
+        assert_eq!(gas.severity(), ErrorSeverity::Invalid);
+
+        let field = ZKsyncTxError::UnsupportedTxField("reserved_dynamic".into());
+        assert!(field.is_l2_native());
+        assert_eq!(field.severity(), ErrorSeverity::Critical);
+
+        let mint = ZKsyncTxError::MintInsufficientForValue {
+            mint: U256::from(1),
+            value: U256::from(2),
+        };
+        assert!(mint.is_l1_to_l2_specific());
+        assert!(!mint.is_l2_native());
+        assert_eq!(mint.severity(), ErrorSeverity::Invalid);
+    }
 }